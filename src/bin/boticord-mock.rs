@@ -0,0 +1,77 @@
+//! Standalone mock of BotiCord API.
+//!
+//! ```text
+//! boticord-mock [--host 127.0.0.1] [--port 8080] [--fixtures DIR]
+//!               [--token TOKEN] [--scenario FILE] [--latency-ms MS]
+//! ```
+//!
+//! See [`boticordrs::mock`] for the fixtures layout, scenario format
+//! and admin routes.
+
+use boticordrs::mock::{MockServer, Scenario};
+
+use std::net::{IpAddr, SocketAddr};
+use std::process::exit;
+use std::time::Duration;
+
+const USAGE: &str = "usage: boticord-mock [--host HOST] [--port PORT] [--fixtures DIR] \
+[--token TOKEN] [--scenario FILE] [--latency-ms MS]";
+
+fn main() {
+    let mut host: IpAddr = [127, 0, 0, 1].into();
+    let mut port = 8080;
+    let mut builder = MockServer::builder();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            println!("{}", USAGE);
+            return;
+        }
+        let value = args.next().unwrap_or_else(|| fail(&format!("missing value for {}", arg)));
+        builder = match arg.as_str() {
+            "--host" => {
+                host = value.parse().unwrap_or_else(|_| fail("invalid host"));
+                builder
+            }
+            "--port" => {
+                port = value.parse().unwrap_or_else(|_| fail("invalid port"));
+                builder
+            }
+            "--fixtures" => builder
+                .fixtures_dir(&value)
+                .unwrap_or_else(|e| fail(&format!("failed to load fixtures: {}", e))),
+            "--token" => builder.token(value),
+            "--scenario" => {
+                let scenario = Scenario::from_file(&value)
+                    .unwrap_or_else(|e| fail(&format!("failed to load scenario: {}", e)));
+                builder.scenario(&scenario)
+            }
+            "--latency-ms" => {
+                let ms = value.parse().unwrap_or_else(|_| fail("invalid latency"));
+                builder.latency(Duration::from_millis(ms))
+            }
+            _ => fail(&format!("unknown argument {}", arg)),
+        };
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed runtime");
+
+    runtime.block_on(async move {
+        let server = builder
+            .bind(SocketAddr::new(host, port))
+            .start()
+            .await
+            .unwrap_or_else(|e| fail(&format!("failed to start: {}", e)));
+        println!("BotiCord mock is listening on {}", server.url());
+        std::future::pending::<()>().await;
+    });
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    exit(2)
+}
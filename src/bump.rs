@@ -0,0 +1,236 @@
+//! Server bumps with cooldown tracking.
+//!
+//! BotiCord-service bots bump servers by posting [`ServerStats`] with `up` set.
+//! [`BumpManager`] remembers when every server was bumped, refuses to bump
+//! it again during the cooldown and keeps its state in a [`BumpStore`],
+//! so it survives restarts.
+//!
+//! ```no_run
+//! use boticordrs::BoticordClient;
+//! use boticordrs::bump::BumpManager;
+//! use boticordrs::store::JsonFileStore;
+//! use boticordrs::types::{ServerId, ServerStats};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = BoticordClient::new("your token".to_string(), 2).expect("failed client");
+//!     let store = JsonFileStore::new("bumps.json");
+//!     let manager = BumpManager::new(client, store).expect("failed to load bumps");
+//!
+//!     let stats = ServerStats::builder(ServerId("724663360934772797".to_string()))
+//!         .build()
+//!         .expect("invalid stats");
+//!
+//!     match manager.bump(stats).await {
+//!         Ok(next) => println!("Bumped! Next bump at {:?}", next),
+//!         Err(e) => eprintln!("{}", e),
+//!     }
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::store::{from_unix, to_unix, JsonFileStore, MemoryStore};
+use crate::types::{ServerId, ServerStats};
+use crate::{ApiError, BoticordClient, BoticordError};
+
+/// Cooldown between two bumps of the same server.
+pub const DEFAULT_BUMP_COOLDOWN: Duration = Duration::from_secs(4 * 60 * 60);
+
+/// Shortest wait of [`BumpManager::bump_when_ready`] between two attempts.
+const MIN_BUMP_WAIT: Duration = Duration::from_secs(1);
+
+/// Bump state of a single server.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BumpRecord {
+    /// Unix timestamp (seconds) of the last successful bump.
+    pub last_bump_at: Option<u64>,
+    /// Unix timestamp (seconds) when the server can be bumped again.
+    pub next_bump_at: u64,
+}
+
+/// Storage for [`BumpManager`] state.
+pub trait BumpStore: Send + Sync {
+    /// Loads all saved records.
+    fn load(&self) -> Result<HashMap<ServerId, BumpRecord>, BoticordError>;
+
+    /// Replaces saved records with the given ones.
+    fn save(&self, records: &HashMap<ServerId, BumpRecord>) -> Result<(), BoticordError>;
+}
+
+impl BumpStore for MemoryStore {
+    fn load(&self) -> Result<HashMap<ServerId, BumpRecord>, BoticordError> {
+        Ok(HashMap::new())
+    }
+
+    fn save(&self, _records: &HashMap<ServerId, BumpRecord>) -> Result<(), BoticordError> {
+        Ok(())
+    }
+}
+
+impl BumpStore for JsonFileStore<HashMap<ServerId, BumpRecord>> {
+    fn load(&self) -> Result<HashMap<ServerId, BumpRecord>, BoticordError> {
+        JsonFileStore::load(self)
+    }
+
+    fn save(&self, records: &HashMap<ServerId, BumpRecord>) -> Result<(), BoticordError> {
+        JsonFileStore::save(self, records)
+    }
+}
+
+/// Bumps servers, keeping track of their cooldowns.
+pub struct BumpManager<S = MemoryStore> {
+    client: BoticordClient,
+    store: S,
+    cooldown: Duration,
+    records: Mutex<HashMap<ServerId, BumpRecord>>,
+    // one bump of a server at a time, held across the request
+    servers: std::sync::Mutex<HashMap<ServerId, Arc<Mutex<()>>>>,
+}
+
+impl<S: BumpStore> BumpManager<S> {
+    /// Constructs a new manager, loading the state from `store`.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client used to post bumps
+    /// * `store` - Storage for bump records
+    ///
+    pub fn new(client: BoticordClient, store: S) -> Result<Self, BoticordError> {
+        let records = store.load()?;
+        Ok(BumpManager {
+            client,
+            store,
+            cooldown: DEFAULT_BUMP_COOLDOWN,
+            records: Mutex::new(records),
+            servers: std::sync::Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Overrides the cooldown, [`DEFAULT_BUMP_COOLDOWN`] by default.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Returns the time when the server can be bumped again,
+    /// or `None` if it can be bumped right now.
+    pub async fn next_bump_at(&self, server: &ServerId) -> Option<SystemTime> {
+        let records = self.records.lock().await;
+        records
+            .get(server)
+            .map(|r| from_unix(r.next_bump_at))
+            .filter(|at| *at > SystemTime::now())
+    }
+
+    /// Returns the time of the last successful bump of the server.
+    pub async fn last_bump_at(&self, server: &ServerId) -> Option<SystemTime> {
+        let records = self.records.lock().await;
+        records.get(server).and_then(|r| r.last_bump_at).map(from_unix)
+    }
+
+    /// Bumps the server, returning the time of the next possible bump.
+    ///
+    /// During the cooldown nothing is sent and [`BoticordError::BumpCooldown`]
+    /// is returned. The same error is returned when BotiCord rejects the bump
+    /// as too early with `429`, the next bump is then due after `Retry-After`,
+    /// or a cooldown after the last known bump without it. Bumps of one server
+    /// run one at a time, the records stay readable while a bump is sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - Server stats, `up` is set automatically
+    ///
+    pub async fn bump(&self, mut stats: ServerStats) -> Result<SystemTime, BoticordError> {
+        let server = stats.server_id.clone();
+        let lock = Arc::clone(self.servers.lock().unwrap().entry(server.clone()).or_default());
+        let _bumping = lock.lock().await;
+
+        let now = SystemTime::now();
+        if let Some(next) = self.next_bump_at(&server).await {
+            return Err(BoticordError::BumpCooldown(next));
+        }
+
+        stats.up = true;
+        match self.client.post_server_stats(stats).await {
+            Ok(()) => {
+                let next = now + self.cooldown;
+                self.record(server, |_| BumpRecord {
+                    last_bump_at: Some(to_unix(now)),
+                    next_bump_at: to_unix(next),
+                }).await?;
+                Ok(next)
+            }
+            Err(BoticordError::Api(e)) if e.status.as_u16() == 429 => {
+                let last = self.last_bump_at(&server).await;
+                let next = next_bump_from_error(&e, now).unwrap_or_else(|| {
+                    last.map(|last| last + self.cooldown)
+                        .filter(|next| *next > now)
+                        .unwrap_or(now + self.cooldown)
+                });
+                self.record(server, |_| BumpRecord {
+                    last_bump_at: last.map(to_unix),
+                    next_bump_at: to_unix(next),
+                }).await?;
+                Err(BoticordError::BumpCooldown(next))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replaces the record of the server and saves all records.
+    async fn record<F>(&self, server: ServerId, f: F) -> Result<(), BoticordError>
+        where
+            F: FnOnce(Option<&BumpRecord>) -> BumpRecord,
+    {
+        let mut records = self.records.lock().await;
+        let record = f(records.get(&server));
+        records.insert(server, record);
+        self.store.save(&records)
+    }
+
+    /// Bumps the server, waiting for the cooldown to end first.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - Server stats, `up` is set automatically
+    ///
+    pub async fn bump_when_ready(&self, stats: ServerStats) -> Result<SystemTime, BoticordError> {
+        loop {
+            match self.bump(stats.clone()).await {
+                Err(BoticordError::BumpCooldown(next)) => {
+                    let wait = next.duration_since(SystemTime::now()).unwrap_or_default();
+                    tokio::time::sleep(wait.max(MIN_BUMP_WAIT)).await;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Forgets everything known about the server.
+    pub async fn forget(&self, server: &ServerId) -> Result<(), BoticordError> {
+        let mut records = self.records.lock().await;
+        if records.remove(server).is_some() {
+            self.store.save(&records)?;
+        }
+        self.servers.lock().unwrap().remove(server);
+        Ok(())
+    }
+}
+
+/// Extracts the time of the next possible bump from a "too early" error.
+///
+/// BotiCord rejects early bumps with `429`, the time comes from its
+/// `Retry-After` header. `None` for other errors and without the header,
+/// a `Retry-After` of zero says nothing about the cooldown either.
+pub fn next_bump_from_error(error: &ApiError, now: SystemTime) -> Option<SystemTime> {
+    if error.status.as_u16() != 429 {
+        return None;
+    }
+    error.retry_after.filter(|retry_after| !retry_after.is_zero()).map(|retry_after| now + retry_after)
+}
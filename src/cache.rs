@@ -0,0 +1,528 @@
+//! Caching of GET responses.
+//!
+//! Attach a [`Cache`] with [`BoticordClient::with_cache`] and successful
+//! responses of routes with a TTL are kept in memory. Routes are written as
+//! templates, e.g. `/bot/{}`, the same ones the metrics use.
+//!
+//! Once the TTL is over, an entry is still served for the
+//! stale-while-revalidate window while the client refreshes it in the
+//! background, so callers don't wait for the API. Older entries are
+//! fetched again before answering.
+//!
+//! ```no_run
+//! use boticordrs::cache::Cache;
+//! use boticordrs::BoticordClient;
+//!
+//! use std::time::Duration;
+//!
+//! let cache = Cache::new(1000)
+//!     .with_ttl("/bot/{}", Duration::from_secs(60))
+//!     .with_ttl("/server/{}", Duration::from_secs(60))
+//!     .with_stale_while_revalidate(Duration::from_secs(300));
+//!
+//! let client = BoticordClient::new("your token", 2)
+//!     .expect("failed client")
+//!     .with_cache(cache.clone());
+//!
+//! // after the bot was edited
+//! cache.invalidate("724663360934772797");
+//! ```
+//!
+//! Entries fetched by short code are invalidated by id, and the other way
+//! around, once the bot, server or user itself went through the cache.
+//!
+//! ## Offline operation
+//!
+//! [`Cache::persistent`] keeps every entry in a directory of JSON files as
+//! well, so the cache survives restarts. Files are written by a background
+//! thread, [`Cache::flush`] waits for it. In [offline mode] a call that can't
+//! reach BotiCord (no response, `429` or `5xx`) is answered with the last
+//! known value, whatever its age. The `*_cached` methods of the client tell
+//! such values apart with [`Cached::stale`].
+//!
+//! ```no_run
+//! use boticordrs::cache::Cache;
+//! use boticordrs::BoticordClient;
+//!
+//! use std::time::Duration;
+//!
+//! # async fn run() -> Result<(), boticordrs::BoticordError> {
+//! let cache = Cache::persistent("cache", 10_000)?
+//!     .with_default_ttl(Duration::from_secs(60))
+//!     .with_offline_mode(true);
+//! let client = BoticordClient::new("your token", 2)?.with_cache(cache);
+//!
+//! let bot = client.get_bot_info_cached("724663360934772797".to_string()).await?;
+//! if bot.stale {
+//!     println!("BotiCord is down, showing data from {:?}", bot.stored_at);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Conditional requests
+//!
+//! When BotiCord sends `ETag` or `Last-Modified` with a response, they are
+//! kept with the entry. Expired entries are then revalidated with
+//! `If-None-Match` and `If-Modified-Since`, and a `304 Not Modified` answer
+//! renews the entry without downloading it again.
+//!
+//! [`BoticordClient::with_cache`]: crate::BoticordClient::with_cache
+//! [offline mode]: Cache::with_offline_mode
+
+use http::header::{HeaderMap, HeaderName, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::store::{fnv1a, from_unix, save_json, to_unix};
+use crate::transport::HttpResponse;
+use crate::BoticordError;
+
+/// Value returned by the `*_cached` methods of the client.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cached<T> {
+    /// Returned value.
+    pub value: T,
+    /// Whether the value is older than the TTL of its route.
+    pub stale: bool,
+    /// When the value was fetched, `None` if it was just fetched.
+    pub stored_at: Option<SystemTime>,
+}
+
+/// Shared cache of GET responses.
+///
+/// Clones share the same entries.
+#[derive(Clone)]
+pub struct Cache {
+    max_entries: usize,
+    default_ttl: Option<Duration>,
+    ttls: HashMap<String, Duration>,
+    stale_while_revalidate: Duration,
+    offline: bool,
+    disk: Option<Arc<Disk>>,
+    entries: Arc<Mutex<Entries>>,
+}
+
+#[derive(Default)]
+struct Entries {
+    /// URL → entry.
+    map: HashMap<String, Entry>,
+    /// Short code or other name → id of the bot, server or user.
+    aliases: HashMap<String, String>,
+    /// Incremented on every access, for LRU eviction.
+    clock: u64,
+}
+
+/// Queue of the thread writing a persistent cache to its directory.
+struct Disk {
+    writes: mpsc::Sender<Write>,
+    /// First failure since the last flush.
+    failure: Arc<Mutex<Option<BoticordError>>>,
+}
+
+/// Change to the directory, applied in order.
+enum Write {
+    Save(DiskEntry),
+    Remove(String),
+    Flush(mpsc::Sender<()>),
+}
+
+struct Entry {
+    route: String,
+    id: Option<String>,
+    response: HttpResponse,
+    stored_at: SystemTime,
+    used_at: u64,
+    refreshing: bool,
+}
+
+/// Entry as it is written to disk.
+#[derive(Deserialize, Serialize)]
+struct DiskEntry {
+    url: String,
+    route: String,
+    id: Option<String>,
+    stored_at: u64,
+    status: u16,
+    body: String,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    last_modified: Option<String>,
+}
+
+/// Names of the bot, server or user in a response body.
+#[derive(Deserialize)]
+struct Names {
+    id: String,
+    #[serde(rename = "shortCode")]
+    short_code: Option<String>,
+}
+
+/// Result of a cache lookup.
+pub(crate) enum Lookup {
+    /// Entry is within its TTL.
+    Fresh(Hit),
+    /// Entry is within the stale-while-revalidate window, `refresh` tells
+    /// whether the caller has to refresh it.
+    Stale { hit: Hit, refresh: bool },
+    /// Entry is too old to be served, but can be revalidated or served
+    /// while BotiCord is unreachable.
+    Expired(Hit),
+    /// Nothing usable.
+    Miss,
+}
+
+/// Cached response.
+#[derive(Clone)]
+pub(crate) struct Hit {
+    pub(crate) response: HttpResponse,
+    pub(crate) stored_at: SystemTime,
+}
+
+impl Hit {
+    /// Headers that make a request conditional on the cached response being outdated.
+    pub(crate) fn conditions(&self) -> HeaderMap {
+        let mut conditions = HeaderMap::new();
+        for (validator, condition) in [(ETAG, IF_NONE_MATCH), (LAST_MODIFIED, IF_MODIFIED_SINCE)] {
+            if let Some(value) = self.response.headers.get(&validator) {
+                conditions.insert(condition, value.clone());
+            }
+        }
+        conditions
+    }
+}
+
+impl Cache {
+    /// Creates an in-memory cache holding up to `max_entries` responses.
+    ///
+    /// Nothing is cached until TTLs are set.
+    pub fn new(max_entries: usize) -> Self {
+        Cache {
+            max_entries,
+            default_ttl: None,
+            ttls: HashMap::new(),
+            stale_while_revalidate: Duration::ZERO,
+            offline: false,
+            disk: None,
+            entries: Arc::default(),
+        }
+    }
+
+    /// Creates a cache persisted in the directory `dir`, holding up to
+    /// `max_entries` responses.
+    ///
+    /// The directory is created if needed and entries saved by
+    /// a previous run are loaded.
+    pub fn persistent(dir: impl Into<PathBuf>, max_entries: usize) -> Result<Self, BoticordError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(BoticordError::Store)?;
+
+        let mut loaded = Vec::new();
+        for file in std::fs::read_dir(&dir).map_err(BoticordError::Store)? {
+            let path = file.map_err(BoticordError::Store)?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                let data = std::fs::read(&path).map_err(BoticordError::Store)?;
+                match serde_json::from_slice::<DiskEntry>(&data) {
+                    Ok(entry) => loaded.push(entry),
+                    // not ours or half-written, it will be overwritten
+                    Err(_) => continue,
+                }
+            }
+        }
+        loaded.sort_by_key(|e| e.stored_at);
+
+        let cache = Cache { disk: Some(Arc::new(Disk::start(dir)?)), ..Cache::new(max_entries) };
+        for entry in loaded {
+            let mut headers = HeaderMap::new();
+            for (name, value) in [(ETAG, entry.etag), (LAST_MODIFIED, entry.last_modified)] {
+                if let Some(value) = value.and_then(|v| HeaderValue::from_str(&v).ok()) {
+                    headers.insert(name, value);
+                }
+            }
+            let response = HttpResponse {
+                status: StatusCode::from_u16(entry.status).unwrap_or(StatusCode::OK),
+                headers,
+                body: entry.body.into_bytes(),
+            };
+            cache.insert(entry.url, entry.route, entry.id, response, from_unix(entry.stored_at), false);
+        }
+        Ok(cache)
+    }
+
+    /// Caches responses of the route, e.g. `/bot/{}`, for `ttl`.
+    pub fn with_ttl(mut self, route: &str, ttl: Duration) -> Self {
+        self.ttls.insert(route.to_string(), ttl);
+        self
+    }
+
+    /// Caches responses of routes without their own TTL for `ttl`.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+        self
+    }
+
+    /// Serves expired entries for `window` while refreshing them in the background.
+    pub fn with_stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = window;
+        self
+    }
+
+    /// Answers with the last known value, whatever its age,
+    /// when BotiCord can't be reached.
+    ///
+    /// Expired entries are kept until they are evicted.
+    pub fn with_offline_mode(mut self, enabled: bool) -> Self {
+        self.offline = enabled;
+        self
+    }
+
+    /// TTL of the route, `None` if it is not cached.
+    pub fn ttl(&self, route: &str) -> Option<Duration> {
+        self.ttls.get(route).copied().or(self.default_ttl)
+    }
+
+    /// Whether offline mode is enabled.
+    pub fn is_offline_mode(&self) -> bool {
+        self.offline
+    }
+
+    /// Drops every entry of the bot, server or user with this id or short code.
+    pub fn invalidate(&self, id: &str) {
+        let mut entries = self.entries();
+        let id = entries.aliases.get(id).map_or(id, String::as_str);
+        let urls: Vec<String> = entries.map.iter()
+            .filter(|(_, e)| {
+                e.id.as_deref().map(|name| entries.aliases.get(name).map_or(name, String::as_str)) == Some(id)
+            })
+            .map(|(url, _)| url.clone())
+            .collect();
+        for url in urls {
+            entries.map.remove(&url);
+            self.remove_file(&url);
+        }
+    }
+
+    /// Drops every entry.
+    pub fn clear(&self) {
+        let mut entries = self.entries();
+        for url in entries.map.keys() {
+            self.remove_file(url);
+        }
+        entries.map.clear();
+        entries.aliases.clear();
+    }
+
+    /// Waits until the entries are written to disk, returning the first
+    /// failure since the last flush.
+    ///
+    /// Blocks the thread. Does nothing for in-memory caches.
+    pub fn flush(&self) -> Result<(), BoticordError> {
+        let disk = match &self.disk {
+            Some(disk) => disk,
+            None => return Ok(()),
+        };
+        let (done, flushed) = mpsc::channel();
+        if disk.writes.send(Write::Flush(done)).is_ok() {
+            let _ = flushed.recv();
+        }
+        match disk.failure.lock().unwrap().take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries().map.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn lookup(&self, url: &str) -> Lookup {
+        let window = self.stale_while_revalidate;
+        let mut entries = self.entries();
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        let entry = match entries.map.get_mut(url) {
+            Some(entry) => entry,
+            None => return Lookup::Miss,
+        };
+        let ttl = match self.ttl(&entry.route) {
+            Some(ttl) => ttl,
+            None => return Lookup::Miss,
+        };
+        let age = entry.stored_at.elapsed().unwrap_or_default();
+        let hit = Hit { response: entry.response.clone(), stored_at: entry.stored_at };
+        if age < ttl {
+            entry.used_at = clock;
+            Lookup::Fresh(hit)
+        } else if age < ttl + window {
+            entry.used_at = clock;
+            let refresh = !entry.refreshing;
+            entry.refreshing = true;
+            Lookup::Stale { hit, refresh }
+        } else if self.offline || !hit.conditions().is_empty() {
+            Lookup::Expired(hit)
+        } else {
+            entries.map.remove(url);
+            self.remove_file(url);
+            Lookup::Miss
+        }
+    }
+
+    pub(crate) fn store(&self, url: &str, route: &str, id: Option<&str>, response: &HttpResponse) {
+        if self.ttl(route).is_none() {
+            return;
+        }
+        let id = id.map(str::to_string);
+        self.insert(url.to_string(), route.to_string(), id, response.clone(), SystemTime::now(), true)
+    }
+
+    /// Renews the entry after BotiCord answered that it is not modified.
+    pub(crate) fn renew(&self, url: &str) {
+        let mut entries = self.entries();
+        let entry = match entries.map.get_mut(url) {
+            Some(entry) => entry,
+            None => return,
+        };
+        entry.stored_at = SystemTime::now();
+        entry.refreshing = false;
+        if let Some(disk) = &self.disk {
+            disk.write(Write::Save(DiskEntry::new(url, entry)));
+        }
+    }
+
+    /// Lets the next lookup refresh the entry again after a failed refresh.
+    pub(crate) fn refresh_failed(&self, url: &str) {
+        if let Some(entry) = self.entries().map.get_mut(url) {
+            entry.refreshing = false;
+        }
+    }
+
+    fn insert(
+        &self,
+        url: String,
+        route: String,
+        id: Option<String>,
+        response: HttpResponse,
+        stored_at: SystemTime,
+        persist: bool,
+    ) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries();
+        entries.clock += 1;
+        if let Ok(names) = serde_json::from_slice::<Names>(&response.body) {
+            for name in [id.clone(), names.short_code].into_iter().flatten() {
+                if name != names.id {
+                    entries.aliases.insert(name, names.id.clone());
+                }
+            }
+        }
+        let entry = Entry { route, id, response, stored_at, used_at: entries.clock, refreshing: false };
+        if let (Some(disk), true) = (&self.disk, persist) {
+            disk.write(Write::Save(DiskEntry::new(&url, &entry)));
+        }
+        entries.map.insert(url, entry);
+
+        while entries.map.len() > self.max_entries {
+            let oldest = entries
+                .map
+                .iter()
+                .min_by_key(|(_, e)| e.used_at)
+                .map(|(url, _)| url.clone())
+                .expect("cache is not empty");
+            entries.map.remove(&oldest);
+            self.remove_file(&oldest);
+        }
+    }
+
+    fn remove_file(&self, url: &str) {
+        if let Some(disk) = &self.disk {
+            disk.write(Write::Remove(url.to_string()));
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap()
+    }
+}
+
+impl Disk {
+    fn start(dir: PathBuf) -> Result<Self, BoticordError> {
+        let (writes, queue) = mpsc::channel();
+        let failure = Arc::new(Mutex::new(None));
+        let writer = Writer { dir, failure: Arc::clone(&failure) };
+        // ends once every clone of the cache is dropped
+        std::thread::Builder::new()
+            .name("boticordrs-cache".to_string())
+            .spawn(move || queue.into_iter().for_each(|write| writer.apply(write)))
+            .map_err(BoticordError::Store)?;
+        Ok(Disk { writes, failure })
+    }
+
+    fn write(&self, write: Write) {
+        // the writer only stops when the last sender is dropped
+        let _ = self.writes.send(write);
+    }
+}
+
+/// Applies the writes of a persistent cache.
+struct Writer {
+    dir: PathBuf,
+    failure: Arc<Mutex<Option<BoticordError>>>,
+}
+
+impl Writer {
+    fn apply(&self, write: Write) {
+        let result = match write {
+            Write::Save(entry) => save_json(&file_name(&self.dir, &entry.url), &entry),
+            Write::Remove(url) => match std::fs::remove_file(file_name(&self.dir, &url)) {
+                // a missing file is as good as a removed one
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(BoticordError::Store(e)),
+                _ => Ok(()),
+            },
+            Write::Flush(done) => {
+                let _ = done.send(());
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            self.failure.lock().unwrap().get_or_insert(e);
+        }
+    }
+}
+
+impl DiskEntry {
+    fn new(url: &str, entry: &Entry) -> Self {
+        let header = |name: HeaderName| {
+            entry.response.headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+        };
+        DiskEntry {
+            url: url.to_string(),
+            route: entry.route.clone(),
+            id: entry.id.clone(),
+            stored_at: to_unix(entry.stored_at),
+            status: entry.response.status.as_u16(),
+            body: String::from_utf8_lossy(&entry.response.body).into_owned(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+}
+
+/// File of the entry, named by the FNV-1a hash of its URL.
+fn file_name(dir: &Path, url: &str) -> PathBuf {
+    dir.join(format!("{:016x}.json", fnv1a(url.as_bytes())))
+}
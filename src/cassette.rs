@@ -0,0 +1,261 @@
+//! Record and replay of the client's HTTP traffic.
+//!
+//! In [`CassetteMode::Record`] every request made by [`BoticordClient`] and its
+//! response are kept and written to a JSON file by [`Cassette::finish`], or
+//! when the cassette is dropped. In [`CassetteMode::Replay`] responses
+//! are served from that file, no network is used, and requests that were not
+//! recorded fail with [`BoticordError::Cassette`].
+//!
+//! The token is never written: the `Authorization` header is not recorded and
+//! every occurrence of the token in URLs and bodies is replaced with
+//! [`REDACTED`].
+//!
+//! ```no_run
+//! use boticordrs::BoticordClient;
+//! use boticordrs::cassette::Cassette;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let cassette = Cassette::replay("tests/cassettes/bot_info.json").expect("failed cassette");
+//!     let client = BoticordClient::new("".to_string(), 2)
+//!         .expect("failed client")
+//!         .with_cassette(cassette);
+//!
+//!     let bot = client.get_bot_info("724663360934772797".to_string()).await.expect("not recorded");
+//!     println!("{:?}", bot.information.short_description);
+//! }
+//! ```
+//!
+//! [`BoticordClient`]: crate::BoticordClient
+
+use http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, TRANSFER_ENCODING};
+use http::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::store::{load_json, save_json};
+use crate::token::Token;
+use crate::transport::HttpResponse;
+use crate::BoticordError;
+
+pub use crate::token::REDACTED;
+
+/// What the cassette does with requests.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CassetteMode {
+    /// Send requests and write them down.
+    Record,
+    /// Serve recorded responses.
+    Replay,
+}
+
+/// Request written to a cassette.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CassetteRequest {
+    /// HTTP method.
+    pub method: String,
+    /// Path relative to the base URL, e.g. `/v2/bot/123`.
+    pub path: String,
+    /// JSON body, if any.
+    pub body: Option<Value>,
+}
+
+/// Response written to a cassette.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CassetteResponse {
+    /// Response status.
+    pub status: u16,
+    /// Response headers.
+    pub headers: Vec<(String, String)>,
+    /// Response body.
+    pub body: String,
+}
+
+/// Single request and its response.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Interaction {
+    /// Request.
+    pub request: CassetteRequest,
+    /// Response.
+    pub response: CassetteResponse,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// File with recorded interactions.
+#[derive(Debug)]
+pub struct Cassette {
+    mode: CassetteMode,
+    path: PathBuf,
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+    unsaved: AtomicBool,
+}
+
+impl Cassette {
+    /// Records interactions to `path`, overwriting the file.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Cassette {
+            mode: CassetteMode::Record,
+            path: path.into(),
+            interactions: Mutex::new(Vec::new()),
+            unsaved: AtomicBool::new(false),
+        }
+    }
+
+    /// Replays interactions recorded in `path`.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, BoticordError> {
+        let path = path.into();
+        if !path.exists() {
+            return Err(BoticordError::Cassette(format!("{} does not exist", path.display())));
+        }
+        let file: CassetteFile = load_json(&path)?;
+        Ok(Cassette {
+            mode: CassetteMode::Replay,
+            path,
+            interactions: Mutex::new(file.interactions.into_iter().map(|i| (i, false)).collect()),
+            unsaved: AtomicBool::new(false),
+        })
+    }
+
+    /// Mode of the cassette.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// File of the cassette.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns all recorded (or loaded) interactions.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().iter().map(|(i, _)| i.clone()).collect()
+    }
+
+    /// Returns interactions that were loaded but never replayed.
+    pub fn unused(&self) -> Vec<Interaction> {
+        self.interactions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, used)| !used)
+            .map(|(i, _)| i.clone())
+            .collect()
+    }
+
+    /// Writes the recorded interactions to the file, does nothing in replay mode.
+    ///
+    /// Called when the cassette is dropped, but errors are only reported here.
+    pub fn finish(&self) -> Result<(), BoticordError> {
+        if self.mode != CassetteMode::Record {
+            return Ok(());
+        }
+        let interactions = self.interactions.lock().unwrap();
+        let file = CassetteFile {
+            interactions: interactions.iter().map(|(i, _)| i.clone()).collect(),
+        };
+        save_json(&self.path, &file)?;
+        self.unsaved.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Finds the first unused interaction matching the request
+    /// and turns its response into [`HttpResponse`].
+    pub(crate) fn replay_request(&self, request: &CassetteRequest) -> Result<HttpResponse, BoticordError> {
+        let mut interactions = self.interactions.lock().unwrap();
+        let found = interactions
+            .iter_mut()
+            .find(|(i, used)| !used && i.request == *request);
+        match found {
+            Some((interaction, used)) => {
+                *used = true;
+                to_response(&interaction.response)
+            }
+            None => Err(BoticordError::Cassette(format!(
+                "no recorded interaction for {} {}",
+                request.method, request.path
+            ))),
+        }
+    }
+
+    /// Writes down the request and its response.
+    pub(crate) fn record_response(
+        &self,
+        request: CassetteRequest,
+        response: &HttpResponse,
+        token: &Token,
+    ) {
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(k, _)| *k != CONTENT_LENGTH && *k != TRANSFER_ENCODING)
+            .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        let interaction = Interaction {
+            request: redact_request(request, token),
+            response: CassetteResponse {
+                status: response.status.as_u16(),
+                headers,
+                body: token.redact(&String::from_utf8_lossy(&response.body)),
+            },
+        };
+
+        self.interactions.lock().unwrap().push((interaction, true));
+        self.unsaved.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        if self.unsaved.load(Ordering::SeqCst) {
+            let _ = self.finish();
+        }
+    }
+}
+
+impl CassetteRequest {
+    pub(crate) fn new(method: &Method, path: &str, body: Option<&Value>) -> Self {
+        CassetteRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            body: body.cloned(),
+        }
+    }
+}
+
+fn redact_request(request: CassetteRequest, token: &Token) -> CassetteRequest {
+    let body = request.body.map(|body| {
+        let text = token.redact(&body.to_string());
+        serde_json::from_str(&text).unwrap_or(Value::String(text))
+    });
+    CassetteRequest {
+        method: request.method,
+        path: token.redact(&request.path),
+        body,
+    }
+}
+
+fn to_response(recorded: &CassetteResponse) -> Result<HttpResponse, BoticordError> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &recorded.headers {
+        let name = HeaderName::from_bytes(name.as_bytes());
+        let value = HeaderValue::from_str(value);
+        if let (Ok(name), Ok(value)) = (name, value) {
+            headers.append(name, value);
+        }
+    }
+    let status = StatusCode::from_u16(recorded.status)
+        .map_err(|e| BoticordError::Cassette(e.to_string()))?;
+    Ok(HttpResponse {
+        status,
+        headers,
+        body: recorded.body.clone().into_bytes(),
+    })
+}
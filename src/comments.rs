@@ -0,0 +1,277 @@
+//! Comments in chunks, sorted and filtered.
+//!
+//! The `*_comments_stream` methods of [`BoticordClient`] return a
+//! [`CommentStream`] of chunks instead of one big list. [`CommentQuery`] sets
+//! the chunk size, the order and which comments to keep.
+//!
+//! BotiCord has no pagination for comments: the first poll of a stream
+//! fetches every comment in one request, and the stream only splits the
+//! sorted and filtered list into chunks. It neither saves the request nor
+//! memory, it is a way to process comments in batches.
+//!
+//! ```no_run
+//! use boticordrs::comments::{CommentOrder, CommentQuery};
+//! use boticordrs::BoticordClient;
+//! use futures_util::StreamExt;
+//!
+//! # async fn run(client: BoticordClient) -> Result<(), boticordrs::BoticordError> {
+//! let query = CommentQuery::new().order(CommentOrder::Newest).chunk_size(20).vote(1);
+//! let mut chunks = client.get_bot_comments_stream("724663360934772797".to_string(), query);
+//! while let Some(chunk) = chunks.next().await {
+//!     for comment in chunk? {
+//!         println!("{}: {}", comment.user_id, comment.text);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use futures_core::Stream;
+use serde::Deserialize;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::types::{BotId, ServerId, SingleComment};
+use crate::watcher::CommentTarget;
+use crate::{get_with_meta, BoticordClient, BoticordError, Endpoint};
+
+/// Chunk size of [`CommentQuery::new`].
+pub const DEFAULT_CHUNK_SIZE: usize = 50;
+
+/// Chunk of comments, or the error that ended the stream.
+pub type CommentChunk<T = SingleComment> = Result<Vec<T>, BoticordError>;
+
+/// Stream of comment chunks, ends after the last chunk or the first error.
+pub type CommentStream<'a, T = SingleComment> = Pin<Box<dyn Stream<Item = CommentChunk<T>> + Send + 'a>>;
+
+/// Comment of a user with the page it is on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserComment {
+    /// Bot or server page of the comment.
+    ///
+    /// BotiCord names the page with the `botID` or `serverID` field of the
+    /// comment, the id is empty if the response leaves it out.
+    pub target: CommentTarget,
+    /// The comment.
+    pub comment: SingleComment,
+}
+
+impl AsRef<SingleComment> for SingleComment {
+    fn as_ref(&self) -> &SingleComment {
+        self
+    }
+}
+
+impl AsRef<SingleComment> for UserComment {
+    fn as_ref(&self) -> &SingleComment {
+        &self.comment
+    }
+}
+
+/// Comment of a user on a bot page, as BotiCord sends it.
+#[derive(Deserialize)]
+struct BotComment {
+    #[serde(rename = "botID", default)]
+    bot: Option<BotId>,
+    #[serde(flatten)]
+    comment: SingleComment,
+}
+
+/// Comment of a user on a server page, as BotiCord sends it.
+#[derive(Deserialize)]
+struct ServerComment {
+    #[serde(rename = "serverID", default)]
+    server: Option<ServerId>,
+    #[serde(flatten)]
+    comment: SingleComment,
+}
+
+/// Comments of a user, as BotiCord sends them.
+#[derive(Deserialize)]
+struct UserCommentsWire {
+    bots: Option<Vec<BotComment>>,
+    servers: Option<Vec<ServerComment>>,
+}
+
+/// Order of streamed comments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CommentOrder {
+    /// As returned by BotiCord.
+    #[default]
+    Api,
+    /// Newest first, comments without a date last.
+    Newest,
+    /// Oldest first, comments without a date last.
+    Oldest,
+    /// Highest vote first.
+    HighestVote,
+    /// Lowest vote first.
+    LowestVote,
+}
+
+/// Chunk size, order and filters of streamed comments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommentQuery {
+    chunk_size: usize,
+    order: CommentOrder,
+    vote: Option<u64>,
+    author: Option<String>,
+    since: Option<SystemTime>,
+    until: Option<SystemTime>,
+}
+
+impl Default for CommentQuery {
+    fn default() -> Self {
+        CommentQuery {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            order: CommentOrder::Api,
+            vote: None,
+            author: None,
+            since: None,
+            until: None,
+        }
+    }
+}
+
+impl CommentQuery {
+    /// All comments in chunks of [`DEFAULT_CHUNK_SIZE`], in the API order.
+    pub fn new() -> Self {
+        CommentQuery::default()
+    }
+
+    /// Sets how many comments a chunk holds, at least one.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Sets the order of the comments.
+    pub fn order(mut self, order: CommentOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Keeps only comments with this vote.
+    pub fn vote(mut self, vote: u64) -> Self {
+        self.vote = Some(vote);
+        self
+    }
+
+    /// Keeps only comments of this user.
+    pub fn author(mut self, user_id: impl Into<String>) -> Self {
+        self.author = Some(user_id.into());
+        self
+    }
+
+    /// Keeps only comments created at or after the time.
+    pub fn since(mut self, time: SystemTime) -> Self {
+        self.since = Some(time);
+        self
+    }
+
+    /// Keeps only comments created before the time.
+    pub fn until(mut self, time: SystemTime) -> Self {
+        self.until = Some(time);
+        self
+    }
+
+    /// Whether the comment passes the filters.
+    ///
+    /// Comments without a creation date never pass a date filter.
+    pub fn matches(&self, comment: &SingleComment) -> bool {
+        let created_at = comment.created_at;
+        self.vote.is_none_or(|vote| comment.vote == vote)
+            && self.author.as_ref().is_none_or(|author| comment.user_id == *author)
+            && self.since.is_none_or(|since| created_at.is_some_and(|at| at >= millis(since)))
+            && self.until.is_none_or(|until| created_at.is_some_and(|at| at < millis(until)))
+    }
+
+    /// Filters and sorts the comments.
+    pub(crate) fn apply<T: AsRef<SingleComment>>(&self, mut comments: Vec<T>) -> Vec<T> {
+        comments.retain(|comment| self.matches(comment.as_ref()));
+        let created_at = |c: &T| c.as_ref().created_at;
+        let vote = |c: &T| c.as_ref().vote;
+        // stable, so equal comments keep the API order
+        match self.order {
+            CommentOrder::Api => {}
+            CommentOrder::Newest => comments.sort_by_key(|c| (created_at(c).is_none(), std::cmp::Reverse(created_at(c)))),
+            CommentOrder::Oldest => comments.sort_by_key(|c| (created_at(c).is_none(), created_at(c))),
+            CommentOrder::HighestVote => comments.sort_by_key(|c| std::cmp::Reverse(vote(c))),
+            CommentOrder::LowestVote => comments.sort_by_key(vote),
+        }
+        comments
+    }
+}
+
+/// Time as a BotiCord timestamp, in milliseconds.
+fn millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+enum Chunks<F, T> {
+    Fetch(F, CommentQuery),
+    Split(std::vec::IntoIter<T>, usize),
+    Done,
+}
+
+/// Chunks of the comments returned by `fetch`.
+fn chunked<'a, F, T>(fetch: F, query: CommentQuery) -> CommentStream<'a, T>
+    where
+        F: Future<Output = Result<Vec<T>, BoticordError>> + Send + 'a,
+        T: AsRef<SingleComment> + Send + 'a,
+{
+    fn next_chunk<F, T>(
+        mut comments: std::vec::IntoIter<T>,
+        chunk_size: usize,
+    ) -> Option<(CommentChunk<T>, Chunks<F, T>)> {
+        let chunk: Vec<_> = comments.by_ref().take(chunk_size).collect();
+        (!chunk.is_empty()).then(|| (Ok(chunk), Chunks::Split(comments, chunk_size)))
+    }
+
+    Box::pin(futures_util::stream::unfold(Chunks::Fetch(fetch, query), |chunks| async move {
+        match chunks {
+            Chunks::Fetch(fetch, query) => match fetch.await {
+                Ok(comments) => next_chunk(query.apply(comments).into_iter(), query.chunk_size),
+                Err(e) => Some((Err(e), Chunks::Done)),
+            },
+            Chunks::Split(comments, chunk_size) => next_chunk(comments, chunk_size),
+            Chunks::Done => None,
+        }
+    }))
+}
+
+impl BoticordClient {
+    /// Same as [`get_bot_comments`](Self::get_bot_comments), in chunks.
+    pub fn get_bot_comments_stream(&self, bot: String, query: CommentQuery) -> CommentStream<'_> {
+        chunked(self.get_bot_comments(bot), query)
+    }
+
+    /// Same as [`get_server_comments`](Self::get_server_comments), in chunks.
+    pub fn get_server_comments_stream(&self, server: String, query: CommentQuery) -> CommentStream<'_> {
+        chunked(self.get_server_comments(server), query)
+    }
+
+    /// Same as [`get_user_comments`](Self::get_user_comments), in chunks,
+    /// every comment tagged with its page.
+    ///
+    /// Comments on bot pages come before comments on server pages,
+    /// unless the query sets another order.
+    pub fn get_user_comments_stream(&self, user: String, query: CommentQuery) -> CommentStream<'_, UserComment> {
+        let fetch = async move {
+            let endpoint = endpoint!(self, "get_user_comments", "/profile/{}/comments", user);
+            let comments: UserCommentsWire = get_with_meta(self, endpoint).await?.value;
+            let bots = comments.bots.into_iter().flatten().map(|c| UserComment {
+                target: CommentTarget::Bot(c.bot.unwrap_or_else(|| BotId(String::new()))),
+                comment: c.comment,
+            });
+            let servers = comments.servers.into_iter().flatten().map(|c| UserComment {
+                target: CommentTarget::Server(c.server.unwrap_or_else(|| ServerId(String::new()))),
+                comment: c.comment,
+            });
+            Ok(bots.chain(servers).collect())
+        };
+        chunked(fetch, query)
+    }
+}
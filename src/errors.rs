@@ -1,19 +1,111 @@
-use reqwest::StatusCode;
+use http::header::RETRY_AFTER;
+use http::StatusCode;
 use url::ParseError;
 
 use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::response::{self, RateLimit};
+use crate::token::Token;
+use crate::transport::HttpResponse;
 
 #[derive(Debug)]
 pub enum BoticordError {
+    #[cfg(feature = "reqwest")]
     Reqwest(reqwest::Error),
     Url(ParseError),
+    /// Custom transport failed to get a response.
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+    /// Response body is not what was expected.
+    Decode(serde_json::Error),
+    /// Data passed to the client is invalid and was not sent.
+    Validation(String),
+    /// BotiCord answered with an error status.
+    Api(ApiError),
+    /// Server can't be bumped until the given time.
+    BumpCooldown(SystemTime),
+    /// Persistent store failed to load or save its state.
+    Store(std::io::Error),
+    /// Request can't be replayed from the cassette.
+    Cassette(String),
+    /// Token was rejected and looks like it is meant for another API version.
+    TokenMismatch(Box<TokenMismatch>),
+    /// Call took longer than its timeout.
+    Timeout(Duration),
+    /// Call was cancelled with its cancellation token.
+    Cancelled,
+}
+
+/// Error response returned by BotiCord API.
+#[derive(Clone, Debug)]
+pub struct ApiError {
+    /// Response status.
+    pub status: StatusCode,
+    /// Value of the `Retry-After` header, if any.
+    pub retry_after: Option<Duration>,
+    /// Raw response body.
+    pub body: String,
+    /// Rate limit state reported by the response, if any.
+    pub rate_limit: Option<RateLimit>,
+    /// ID of the request assigned by BotiCord or its proxy, if any.
+    pub request_id: Option<String>,
+}
+
+/// Token of one API version used with another.
+#[derive(Clone, Debug)]
+pub struct TokenMismatch {
+    /// API version of the client.
+    pub version: u64,
+    /// API versions the token looks like it was issued for.
+    pub token_versions: &'static [u64],
+    /// Response that rejected the token.
+    pub error: ApiError,
 }
 
 impl BoticordError {
     pub fn status(&self) -> Option<StatusCode> {
         match self {
+            #[cfg(feature = "reqwest")]
             BoticordError::Reqwest(e) => e.status(),
-            BoticordError::Url(_) => None,
+            BoticordError::Api(e) => Some(e.status),
+            BoticordError::TokenMismatch(e) => Some(e.error.status),
+            BoticordError::Url(_)
+            | BoticordError::Transport(_)
+            | BoticordError::Decode(_)
+            | BoticordError::Validation(_)
+            | BoticordError::BumpCooldown(_)
+            | BoticordError::Store(_)
+            | BoticordError::Cassette(_)
+            | BoticordError::Timeout(_)
+            | BoticordError::Cancelled => None,
+        }
+    }
+
+    /// Rate limit state reported by the error response, if any.
+    pub fn rate_limit(&self) -> Option<&RateLimit> {
+        match self {
+            BoticordError::Api(e) => e.rate_limit.as_ref(),
+            BoticordError::TokenMismatch(e) => e.error.rate_limit.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Short name of the variant, e.g. `"api"` or `"decode"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "reqwest")]
+            BoticordError::Reqwest(_) => "reqwest",
+            BoticordError::Url(_) => "url",
+            BoticordError::Transport(_) => "transport",
+            BoticordError::Decode(_) => "decode",
+            BoticordError::Validation(_) => "validation",
+            BoticordError::Api(_) => "api",
+            BoticordError::BumpCooldown(_) => "bump_cooldown",
+            BoticordError::Store(_) => "store",
+            BoticordError::Cassette(_) => "cassette",
+            BoticordError::TokenMismatch(_) => "token_mismatch",
+            BoticordError::Timeout(_) => "timeout",
+            BoticordError::Cancelled => "cancelled",
         }
     }
 }
@@ -23,12 +115,78 @@ impl std::error::Error for BoticordError {}
 impl fmt::Display for BoticordError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            #[cfg(feature = "reqwest")]
             BoticordError::Reqwest(e) => e.fmt(f),
             BoticordError::Url(e) => e.fmt(f),
+            BoticordError::Transport(e) => write!(f, "transport error: {}", e),
+            BoticordError::Decode(e) => write!(f, "failed to decode response: {}", e),
+            BoticordError::Validation(e) => write!(f, "validation error: {}", e),
+            BoticordError::Api(e) => write!(f, "BotiCord API error ({}): {}", e.status, e.body),
+            BoticordError::BumpCooldown(at) => {
+                let left = at.duration_since(SystemTime::now()).unwrap_or_default();
+                write!(f, "server can be bumped again in {}s", left.as_secs())
+            }
+            BoticordError::Store(e) => write!(f, "store error: {}", e),
+            BoticordError::Cassette(e) => write!(f, "cassette error: {}", e),
+            BoticordError::TokenMismatch(e) => write!(
+                f,
+                "token was rejected: it looks like a BotiCord API {} token, but the client uses v{}",
+                e.token_versions.iter().map(|v| format!("v{}", v)).collect::<Vec<_>>().join("/"),
+                e.version,
+            ),
+            BoticordError::Timeout(timeout) => write!(f, "call timed out after {:?}", timeout),
+            BoticordError::Cancelled => f.write_str("call was cancelled"),
         }
     }
 }
 
+#[cfg(feature = "reqwest")]
 pub fn from(e: reqwest::Error) -> BoticordError {
     BoticordError::Reqwest(e)
-}
\ No newline at end of file
+}
+
+pub fn from_response(resp: &HttpResponse, token: &Token, version: u64) -> BoticordError {
+    let error = ApiError {
+        status: resp.status,
+        retry_after: retry_after(resp),
+        body: token.redact(&String::from_utf8_lossy(&resp.body)),
+        rate_limit: RateLimit::from_headers(&resp.headers),
+        request_id: response::request_id(&resp.headers),
+    };
+    let token_versions = token.guess_versions();
+    if resp.status == StatusCode::UNAUTHORIZED && !token_versions.is_empty() && !token_versions.contains(&version) {
+        BoticordError::TokenMismatch(Box::new(TokenMismatch { version, token_versions, error }))
+    } else {
+        BoticordError::Api(error)
+    }
+}
+
+/// Copy of the error for callers sharing one request.
+///
+/// Errors of `reqwest` can't be copied and become [`BoticordError::Transport`]
+/// with the same message.
+pub fn duplicate(e: &BoticordError) -> BoticordError {
+    match e {
+        #[cfg(feature = "reqwest")]
+        BoticordError::Reqwest(e) => BoticordError::Transport(e.to_string().into()),
+        BoticordError::Url(e) => BoticordError::Url(*e),
+        BoticordError::Transport(e) => BoticordError::Transport(e.to_string().into()),
+        BoticordError::Decode(e) => BoticordError::Decode(serde::de::Error::custom(e)),
+        BoticordError::Validation(e) => BoticordError::Validation(e.clone()),
+        BoticordError::Api(e) => BoticordError::Api(e.clone()),
+        BoticordError::BumpCooldown(at) => BoticordError::BumpCooldown(*at),
+        BoticordError::Store(e) => BoticordError::Store(std::io::Error::new(e.kind(), e.to_string())),
+        BoticordError::Cassette(e) => BoticordError::Cassette(e.clone()),
+        BoticordError::TokenMismatch(e) => BoticordError::TokenMismatch(e.clone()),
+        BoticordError::Timeout(timeout) => BoticordError::Timeout(*timeout),
+        BoticordError::Cancelled => BoticordError::Cancelled,
+    }
+}
+
+pub fn retry_after(resp: &HttpResponse) -> Option<Duration> {
+    resp.headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+        .map(Duration::from_secs)
+}
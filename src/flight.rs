@@ -0,0 +1,83 @@
+//! Coalescing of identical concurrent requests.
+
+use tokio::sync::broadcast;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use crate::errors;
+use crate::transport::HttpResponse;
+use crate::BoticordError;
+
+type Shared = Arc<Result<HttpResponse, BoticordError>>;
+
+/// Requests in flight, by key.
+#[derive(Default)]
+pub(crate) struct SingleFlight {
+    flights: Mutex<HashMap<String, broadcast::Sender<Shared>>>,
+}
+
+impl SingleFlight {
+    /// Runs `request` unless a request with the same key is already in flight,
+    /// in which case its result is shared.
+    pub(crate) async fn run<F, Fut>(&self, key: String, request: F) -> Result<HttpResponse, BoticordError>
+        where
+            F: FnOnce() -> Fut,
+            Fut: Future<Output = Result<HttpResponse, BoticordError>>,
+    {
+        loop {
+            let mut receiver = {
+                let mut flights = self.flights.lock().unwrap();
+                match flights.get(&key) {
+                    Some(sender) => sender.subscribe(),
+                    None => {
+                        flights.insert(key.clone(), broadcast::channel(1).0);
+                        break;
+                    }
+                }
+            };
+            match receiver.recv().await {
+                Ok(shared) => return duplicate(&shared),
+                // the leading request was cancelled, try to lead
+                Err(_) => continue,
+            }
+        }
+
+        let mut flight = Flight { owner: self, key: Some(key) };
+        let result = request().await;
+        let sender = flight.land();
+        if sender.receiver_count() > 0 {
+            let _ = sender.send(Arc::new(duplicate(&result)));
+        }
+        result
+    }
+}
+
+/// Removes the flight when the leading request is done or cancelled.
+struct Flight<'a> {
+    owner: &'a SingleFlight,
+    key: Option<String>,
+}
+
+impl Flight<'_> {
+    fn land(&mut self) -> broadcast::Sender<Shared> {
+        let key = self.key.take().expect("flight landed once");
+        self.owner.flights.lock().unwrap().remove(&key).expect("flight is registered")
+    }
+}
+
+impl Drop for Flight<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.owner.flights.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+fn duplicate(result: &Result<HttpResponse, BoticordError>) -> Result<HttpResponse, BoticordError> {
+    match result {
+        Ok(resp) => Ok(resp.clone()),
+        Err(e) => Err(errors::duplicate(e)),
+    }
+}
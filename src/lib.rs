@@ -17,7 +17,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() {
-//!     let client = BoticordClient::new("your token".to_string(), 2).expect("failed client");
+//!     let client = BoticordClient::new("your token", 2).expect("failed client");
 //!
 //!     let stats = BotStats {servers: 2514, shards: 3, users: 338250};
 //!
@@ -31,31 +31,109 @@
 //! ```
 #![doc(html_root_url = "https://docs.rs/boticordrs/0.1.3")]
 
-use reqwest::header::AUTHORIZATION;
-use reqwest::{Client as ReqwestClient, Response};
-use reqwest::{Method};
+use http::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Method, StatusCode};
+#[cfg(feature = "reqwest")]
+use reqwest::Client as ReqwestClient;
+
+use zeroize::Zeroize;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Default BotiCord API base URL.
+pub const API_URL: &str = "https://api.boticord.top";
 
 macro_rules! api_url {
-    ($e:expr) => {
-        concat!("https://api.boticord.top/v", $e)
+    ($client:expr, $e:expr) => {
+        format!(concat!("{}/v{}", $e), $client.base_url, $client.version)
+    };
+    ($client:expr, $e:expr, $($rest:tt)*) => {
+        format!(concat!("{}/v{}", $e), $client.base_url, $client.version, $($rest)*)
+    };
+}
+
+macro_rules! endpoint {
+    ($client:expr, $name:expr, $route:expr) => {
+        Endpoint {
+            name: $name,
+            route: $route,
+            id: None,
+            url: api_url!($client, $route),
+            headers: $crate::options::current().headers,
+        }
     };
-    ($e:expr, $($rest:tt)*) => {
-        format!(api_url!($e), $($rest)*)
+    ($client:expr, $name:expr, $route:expr, $id:expr) => {
+        Endpoint {
+            name: $name,
+            route: $route,
+            id: Some($id.to_string()),
+            url: api_url!($client, $route, $id),
+            headers: $crate::options::current().headers,
+        }
     };
 }
 
 pub mod types;
+pub mod bump;
+pub mod cache;
+pub mod cassette;
+pub mod comments;
+pub mod options;
+pub mod reminder;
+pub mod response;
+pub mod retry;
+pub mod store;
+pub mod token;
+pub mod transport;
+pub mod watcher;
+#[cfg(feature = "tower")]
+pub mod service;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 mod errors;
+mod flight;
+mod trace;
 
+use cache::{Cache, Cached, Hit, Lookup};
+use cassette::{Cassette, CassetteMode, CassetteRequest};
+use flight::SingleFlight;
+use options::RequestOptions;
+use response::ApiResponse;
+use retry::RetryPolicy;
+use token::{Token, TokenProvider};
+use transport::{HttpRequest, HttpResponse, HttpTransport};
 use types::*;
-pub use errors::BoticordError;
+pub use errors::{ApiError, BoticordError, TokenMismatch};
 
 /// You can use it to make it much easier to use the Boticord API.
 #[derive(Clone)]
 pub struct BoticordClient {
-    client: ReqwestClient,
-    token: String,
-    version: u64
+    transport: Arc<dyn HttpTransport>,
+    token: Arc<dyn TokenProvider>,
+    version: u64,
+    base_url: String,
+    retry: RetryPolicy,
+    cassette: Option<Arc<Cassette>>,
+    cache: Option<Cache>,
+    flights: Option<Arc<SingleFlight>>,
+    #[cfg(feature = "tracing")]
+    log_bodies: bool,
+    #[cfg(feature = "metrics")]
+    metrics: Option<metrics::Metrics>,
+}
+
+impl std::fmt::Debug for BoticordClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoticordClient")
+            .field("token", &format_args!("{}", token::REDACTED))
+            .field("version", &self.version)
+            .field("base_url", &self.base_url)
+            .field("retry", &self.retry)
+            .finish_non_exhaustive()
+    }
 }
 
 impl BoticordClient {
@@ -63,30 +141,190 @@ impl BoticordClient {
     ///
     /// In BotiCord API v2 there are some changes with token.
     /// [Read more here](https://docs.boticord.top/topics/v1vsv2/)
+    /// The token is sent with the scheme of `version`, see [`token::AuthScheme`].
     ///
     /// # Arguments
     ///
-    /// * `token` - Your BotiCord token
-    /// * `version` - Version of BotiCord API.
+    /// * `token` - Your BotiCord token, see [`Token`]
+    /// * `version` - Version of BotiCord API, one of [`token::VERSIONS`].
     ///
-    pub fn new(token: String, version: u64) -> Result<Self, BoticordError> {
+    #[cfg(feature = "reqwest")]
+    pub fn new(token: impl Into<Token>, version: u64) -> Result<Self, BoticordError> {
+        token::AuthScheme::for_version(version)?;
         let client = ReqwestClient::builder().build().map_err(errors::from)?;
-        Ok(BoticordClient { client, token, version })
+        Ok(BoticordClient::new_with_client(client, token, version))
     }
 
     /// Constructs a new Client with ReqwestClient specified by user.
     ///
     /// In BotiCord API v2 there are some changes with token.
     /// [Read more here](https://docs.boticord.top/topics/v1vsv2/)
+    /// The token is sent with the scheme of `version`, see [`token::AuthScheme`].
     ///
     /// # Arguments
     ///
     /// * `client` - Your custom ReqwestClient
-    /// * `token` - Your BotiCord token
+    /// * `token` - Your BotiCord token, see [`Token`]
     /// * `version` - Version of BotiCord API
     ///
-    pub fn new_with_client(client: ReqwestClient, token: String, version: u64) -> Self {
-        BoticordClient { client, token, version }
+    #[cfg(feature = "reqwest")]
+    pub fn new_with_client(client: ReqwestClient, token: impl Into<Token>, version: u64) -> Self {
+        BoticordClient::new_with_transport(client, token, version)
+    }
+
+    /// Constructs a new Client with a custom HTTP transport.
+    ///
+    /// See [`transport`] for more.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - Your HTTP transport
+    /// * `token` - Your BotiCord token, see [`Token`]
+    /// * `version` - Version of BotiCord API
+    ///
+    pub fn new_with_transport<T>(transport: T, token: impl Into<Token>, version: u64) -> Self
+        where
+            T: HttpTransport + 'static,
+    {
+        BoticordClient {
+            transport: Arc::new(transport),
+            token: Arc::new(token.into()),
+            version,
+            base_url: API_URL.to_string(),
+            retry: RetryPolicy::none(),
+            cassette: None,
+            cache: None,
+            flights: Some(Arc::default()),
+            #[cfg(feature = "tracing")]
+            log_bodies: false,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Constructs a new Client over a [`tower::Service`] stack.
+    ///
+    /// See [`service`] for more.
+    ///
+    /// # Arguments
+    ///
+    /// * `service` - Service that sends HTTP requests
+    /// * `token` - Your BotiCord token, see [`Token`]
+    /// * `version` - Version of BotiCord API
+    ///
+    #[cfg(feature = "tower")]
+    pub fn new_with_service<S>(service: S, token: impl Into<Token>, version: u64) -> Self
+        where
+            S: tower::Service<HttpRequest, Response = HttpResponse> + Send + 'static,
+            S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+            S::Future: Send,
+    {
+        BoticordClient::new_with_transport(service::ServiceTransport::new(service), token, version)
+    }
+
+    /// Sets where the token of every request comes from, replacing
+    /// the token passed to the constructor.
+    ///
+    /// See [`token`] for more.
+    pub fn with_token_provider<P: TokenProvider + 'static>(mut self, provider: P) -> Self {
+        self.token = Arc::new(provider);
+        self
+    }
+
+    /// Returns a client that acts with another token.
+    ///
+    /// The returned client shares everything else with this one, so a single
+    /// client can make requests on behalf of several bots.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - Token of the other bot
+    ///
+    pub fn for_token(&self, token: impl Into<Token>) -> Self {
+        BoticordClient { token: Arc::new(token.into()), ..self.clone() }
+    }
+
+    /// Makes the calls awaited by `call` with the options.
+    ///
+    /// See [`options`] for more.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Options of the calls
+    /// * `call` - Makes the calls with this client
+    ///
+    pub async fn with_options<'a, F, Fut>(&'a self, options: RequestOptions, call: F) -> Fut::Output
+        where
+            F: FnOnce(&'a Self) -> Fut,
+            Fut: std::future::Future,
+    {
+        options::scope(options, call(self)).await
+    }
+
+    /// Sets when and how often failed requests are retried.
+    /// Nothing is retried by default.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the API base URL, [`API_URL`] by default.
+    ///
+    /// Useful for proxies and mock servers.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - URL without the version part, e.g. `http://127.0.0.1:8080`
+    ///
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Logs request and response bodies in `TRACE` events.
+    ///
+    /// Disabled by default, as bodies may contain private data.
+    /// The token is never logged.
+    #[cfg(feature = "tracing")]
+    pub fn with_body_logging(mut self, enabled: bool) -> Self {
+        self.log_bodies = enabled;
+        self
+    }
+
+    /// Records calls of this client into the registry.
+    ///
+    /// See [`metrics`] for more.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: metrics::Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Serves GET requests from the cache.
+    ///
+    /// See [`cache`] for more.
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Whether identical GET requests made at the same time share one
+    /// HTTP request. Enabled by default.
+    ///
+    /// Callers sharing a request get the same response, or copies of
+    /// the same error.
+    pub fn with_coalescing(mut self, enabled: bool) -> Self {
+        self.flights = enabled.then(Arc::default);
+        self
+    }
+
+    /// Records requests to the cassette or replays them from it.
+    ///
+    /// Pass an `Arc<Cassette>` to keep a handle for [`Cassette::finish`].
+    /// See [`cassette`] for more.
+    pub fn with_cassette(mut self, cassette: impl Into<Arc<Cassette>>) -> Self {
+        self.cassette = Some(cassette.into());
+        self
     }
 
     /// Get information about a specific bot.
@@ -96,8 +334,7 @@ impl BoticordClient {
     /// * `bot` - Id of bot.
     ///
     pub async fn get_bot_info(&self, bot: String) -> Result<Bot, BoticordError> {
-        let url = api_url!("{}/bot/{}", &self.version, bot);
-        get(self, url).await
+        self.get_bot_info_with_meta(bot).await.map(|r| r.value)
     }
 
     /// Get information about a specific server.
@@ -107,8 +344,7 @@ impl BoticordClient {
     /// * `server` - Id of server.
     ///
     pub async fn get_server_info(&self, server: String) -> Result<Server, BoticordError> {
-        let url = api_url!("{}/server/{}", &self.version, server);
-        get(self, url).await
+        self.get_server_info_with_meta(server).await.map(|r| r.value)
     }
 
     /// Get information about a specific user.
@@ -118,8 +354,34 @@ impl BoticordClient {
     /// * `user` - Id of user.
     ///
     pub async fn get_user_info(&self, user: String) -> Result<UserInformation, BoticordError> {
-        let url = api_url!("{}/profile/{}", &self.version, user);
-        get(self, url).await
+        self.get_user_info_with_meta(user).await.map(|r| r.value)
+    }
+
+    /// Same as [`get_bot_info`](Self::get_bot_info), but tells whether
+    /// the value came from the cache and is stale.
+    ///
+    /// See [`cache`] for more.
+    pub async fn get_bot_info_cached(&self, bot: String) -> Result<Cached<Bot>, BoticordError> {
+        let endpoint = endpoint!(self, "get_bot_info", "/bot/{}", bot);
+        get_cached(self, endpoint).await
+    }
+
+    /// Same as [`get_server_info`](Self::get_server_info), but tells whether
+    /// the value came from the cache and is stale.
+    ///
+    /// See [`cache`] for more.
+    pub async fn get_server_info_cached(&self, server: String) -> Result<Cached<Server>, BoticordError> {
+        let endpoint = endpoint!(self, "get_server_info", "/server/{}", server);
+        get_cached(self, endpoint).await
+    }
+
+    /// Same as [`get_user_info`](Self::get_user_info), but tells whether
+    /// the value came from the cache and is stale.
+    ///
+    /// See [`cache`] for more.
+    pub async fn get_user_info_cached(&self, user: String) -> Result<Cached<UserInformation>, BoticordError> {
+        let endpoint = endpoint!(self, "get_user_info", "/profile/{}", user);
+        get_cached(self, endpoint).await
     }
 
     /// Get Vec of bot's comments.
@@ -129,8 +391,7 @@ impl BoticordClient {
     /// * `bot` - Id of bot.
     ///
     pub async fn get_bot_comments(&self, bot: String) -> Result<Vec<SingleComment>, BoticordError> {
-        let url = api_url!("{}/bot/{}/comments", &self.version, bot);
-        get(self, url).await
+        self.get_bot_comments_with_meta(bot).await.map(|r| r.value)
     }
 
     /// Get Vec of server's comments.
@@ -142,8 +403,7 @@ impl BoticordClient {
     pub async fn get_server_comments(&self,
                                      server: String
     ) -> Result<Vec<SingleComment>, BoticordError> {
-        let url = api_url!("{}/server/{}/comments", &self.version, server);
-        get(self, url).await
+        self.get_server_comments_with_meta(server).await.map(|r| r.value)
     }
 
     /// Get Vec of user's comments.
@@ -155,8 +415,7 @@ impl BoticordClient {
     pub async fn get_user_comments(&self,
                                    user: String
     ) -> Result<UserComments, BoticordError> {
-        let url = api_url!("{}/profile/{}/comments", &self.version, user);
-        get(self, url).await
+        self.get_user_comments_with_meta(user).await.map(|r| r.value)
     }
 
     /// Get Vec of user's bots.
@@ -168,14 +427,12 @@ impl BoticordClient {
     pub async fn get_user_bots(&self,
                                user: String
     ) -> Result<Vec<SingleUserBot>, BoticordError> {
-        let url = api_url!("{}/bots/{}", &self.version, user);
-        get(self, url).await
+        self.get_user_bots_with_meta(user).await.map(|r| r.value)
     }
 
     /// Get Vec of shorted by current user links
     pub async fn get_my_shorted_links(&self) -> Result<Vec<ShortedLink>, BoticordError> {
-        let url = api_url!("{}/links/get", &self.version,);
-        post_with_response(self, url, Some(EmptyBody{})).await
+        self.get_my_shorted_links_with_meta().await.map(|r| r.value)
     }
 
     /// Get Vec of shorted by current user links with the provided code
@@ -187,8 +444,7 @@ impl BoticordClient {
     pub async fn search_for_shorted_link(&self,
                                       shortener_body: ShortenerBody
     ) -> Result<Vec<ShortedLink>, BoticordError> {
-        let url = api_url!("{}/links/get", &self.version,);
-        post_with_response(self, url, Some(shortener_body)).await
+        self.search_for_shorted_link_with_meta(shortener_body).await.map(|r| r.value)
     }
 
     /// Creates new shorted link
@@ -200,8 +456,7 @@ impl BoticordClient {
     pub async fn create_shorted_link(&self,
                                       shortener_body: ShortenerBody
     ) -> Result<ShortedLink, BoticordError> {
-        let url = api_url!("{}/links/create", &self.version,);
-        post_with_response(self, url, Some(shortener_body)).await
+        self.create_shorted_link_with_meta(shortener_body).await.map(|r| r.value)
     }
 
     /// Deletes shorted link
@@ -213,8 +468,7 @@ impl BoticordClient {
     pub async fn delete_shorted_link(&self,
                                      shortener_body: ShortenerBody
     ) -> Result<(), BoticordError> {
-        let url = api_url!("{}/links/delete", &self.version);
-        post(self, url, Some(shortener_body)).await
+        self.delete_shorted_link_with_meta(shortener_body).await.map(|r| r.value)
     }
 
 
@@ -233,8 +487,7 @@ impl BoticordClient {
     /// let stats = BotStats{servers: 2514, shards: 3, users: 338250};
     /// ```
     pub async fn post_bot_stats(&self, stats: BotStats) -> Result<(), BoticordError> {
-        let url = api_url!("{}/stats", &self.version);
-        post(self, url, Some(stats)).await
+        self.post_bot_stats_with_meta(stats).await.map(|r| r.value)
     }
 
     /// Post Server Stats Method.
@@ -248,72 +501,407 @@ impl BoticordClient {
     ///
     /// * `stats` - Stats that we will post
     ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use boticordrs::types::{ServerId, ServerStats, UserId};
+    ///
+    /// let stats = ServerStats::builder(ServerId("724663360934772797".to_string()))
+    ///     .bump(true)
+    ///     .owner(UserId("585766846268047370".to_string()))
+    ///     .members(120, 34)
+    ///     .build()
+    ///     .expect("invalid stats");
+    /// ```
     pub async fn post_server_stats(&self, stats: ServerStats) -> Result<(), BoticordError> {
-        let url = api_url!("{}/server ", &self.version);
-        post(self, url, Some(stats)).await
+        self.post_server_stats_with_meta(stats).await.map(|r| r.value)
+    }
+
+    /// Calls an endpoint this crate has no method for.
+    ///
+    /// `path` is relative to the API version, e.g. `/bot/{id}/comments`. The call
+    /// is authorized, retried and its errors mapped like any other; an empty
+    /// response body is decoded as `null`, so `Resp` can be `()`.
+    /// Telemetry reports raw calls under the route `/{}`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use boticordrs::BoticordClient;
+    /// use boticordrs::types::Bot;
+    /// use http::Method;
+    ///
+    /// # async fn run(client: BoticordClient) -> Result<(), boticordrs::BoticordError> {
+    /// let bot: Bot = client.call::<(), _>(Method::GET, "/bot/724663360934772797", None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call<Req, Resp>(&self, method: Method, path: &str, body: Option<Req>) -> Result<Resp, BoticordError>
+        where
+            Req: serde::Serialize,
+            Resp: serde::de::DeserializeOwned,
+    {
+        self.call_with_meta(method, path, body).await.map(|r| r.value)
+    }
+
+    /// Same as [`call`](Self::call), with the body and the response as JSON values.
+    pub async fn call_value(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, BoticordError> {
+        self.call(method, path, body).await
     }
 }
 
+/// Endpoint of a raw [`call`](BoticordClient::call).
+fn raw_endpoint(client: &BoticordClient, path: &str) -> Result<Endpoint, BoticordError> {
+    // the token must not leave the API host
+    if path.contains("://") || path.starts_with("//") {
+        return Err(BoticordError::Validation(format!("path must be relative to the API, got {}", path)));
+    }
+    let path = path.trim_start_matches('/');
+    url::Url::parse(&api_url!(client, "/{}", path)).map_err(BoticordError::Url)?;
+    Ok(endpoint!(client, "call", "/{}", path))
+}
+
+/// API call being made, used by telemetry and the cache.
+#[derive(Clone)]
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+pub(crate) struct Endpoint {
+    /// Name of the client method.
+    name: &'static str,
+    /// Route template, e.g. `/bot/{}`.
+    route: &'static str,
+    /// Id of the requested resource.
+    id: Option<String>,
+    /// Full URL.
+    url: String,
+    /// Headers added to the request.
+    headers: HeaderMap,
+}
+
 async fn request<T>(
     client: &BoticordClient,
+    endpoint: Endpoint,
     method: Method,
-    url: String,
     data: Option<T>,
-) -> Result<Response, BoticordError>
+) -> Result<HttpResponse, BoticordError>
     where
         T: serde::Serialize + Sized,
 {
+    let span = trace::span(client, &endpoint, &method);
+    #[cfg(feature = "metrics")]
+    let (route, http_method, started) = (endpoint.route, method.clone(), Instant::now());
+    let attempts = trace::instrument(request_attempts(client, endpoint, method, data), span);
+    let result = options::guard(attempts).await;
+    #[cfg(feature = "metrics")]
+    if let Some(metrics) = &client.metrics {
+        metrics.observe(route, &http_method, &result, started.elapsed());
+    }
+    result
+}
 
-    let mut req = client
-        .client
-        .request(method, &url)
-        .header(AUTHORIZATION, &client.token);
+async fn request_attempts<T>(
+    client: &BoticordClient,
+    endpoint: Endpoint,
+    method: Method,
+    data: Option<T>,
+) -> Result<HttpResponse, BoticordError>
+    where
+        T: serde::Serialize + Sized,
+{
+    let started = Instant::now();
+    let body = match data {
+        Some(data) => Some(
+            serde_json::to_value(&data).map_err(|e| BoticordError::Validation(e.to_string()))?,
+        ),
+        None => None,
+    };
+    let token = client.token.token()?;
+    trace::request_body(client, &token, body.as_ref());
 
-    if let Some(data) = data {
-        req = req.json(&data);
+    let mut headers = endpoint.headers;
+    let mut value = token.authorization(client.version)?;
+    let authorization = HeaderValue::from_str(&value);
+    value.zeroize();
+    let mut authorization = authorization
+        .map_err(|_| BoticordError::Validation("token is not a valid header value".to_string()))?;
+    authorization.set_sensitive(true);
+    headers.insert(AUTHORIZATION, authorization);
+    if body.is_some() {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
     }
-
-    let resp = match req.send().await {
-        Ok(resp) => resp,
-        Err(e) => return Err(errors::from(e)),
+    let http_request = HttpRequest {
+        method,
+        url: endpoint.url,
+        headers,
+        body: body.as_ref().map(|b| b.to_string().into_bytes()),
     };
-    match resp.status() {
-        _ => resp.error_for_status().map_err(errors::from),
+
+    let retry = options::current().retry.unwrap_or_else(|| client.retry.clone());
+    // a 429 was never processed, other failures of a POST may have been
+    let retry_failures = retry.retries_failures_of(&http_request.method);
+    let mut attempt = 0;
+    loop {
+        let result = send(client, &token, &http_request, body.as_ref()).await;
+        trace::attempt(attempt + 1, &result);
+        let retry_after = match &result {
+            Ok(resp) if resp.status.as_u16() == 429 => Some(errors::retry_after(resp)),
+            Ok(resp) if retry_failures && resp.status.is_server_error() => Some(errors::retry_after(resp)),
+            Err(e) if retry_failures && is_transport_error(e) => Some(None),
+            _ => None,
+        };
+
+        match retry_after {
+            Some(retry_after) if attempt < retry.max_retries => {
+                tokio::time::sleep(retry.delay(attempt, retry_after)).await;
+                attempt += 1;
+            }
+            _ => {
+                let result = result.and_then(|resp| {
+                    if resp.status.is_client_error() || resp.status.is_server_error() {
+                        Err(errors::from_response(&resp, &token, client.version))
+                    } else {
+                        Ok(resp)
+                    }
+                });
+                trace::finish(client, &token, &result, attempt + 1, started.elapsed());
+                return result;
+            }
+        }
+    }
+}
+
+async fn send(
+    client: &BoticordClient,
+    token: &Token,
+    request: &HttpRequest,
+    body: Option<&serde_json::Value>,
+) -> Result<HttpResponse, BoticordError> {
+    match &client.cassette {
+        Some(cassette) => {
+            let path = request.url.strip_prefix(&client.base_url).unwrap_or(&request.url);
+            let recorded = CassetteRequest::new(&request.method, path, body);
+            match cassette.mode() {
+                CassetteMode::Replay => cassette.replay_request(&recorded),
+                CassetteMode::Record => {
+                    let resp = client.transport.send(request.clone()).await?;
+                    cassette.record_response(recorded, &resp, token);
+                    Ok(resp)
+                }
+            }
+        }
+        None => client.transport.send(request.clone()).await,
     }
 }
 
-async fn get<T>(client: &BoticordClient, url: String) -> Result<T, BoticordError>
+fn is_transport_error(e: &BoticordError) -> bool {
+    match e {
+        #[cfg(feature = "reqwest")]
+        BoticordError::Reqwest(_) => true,
+        BoticordError::Transport(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+fn decode<R>(client: &BoticordClient, route: &'static str, resp: &HttpResponse) -> Result<R, BoticordError>
+    where
+        R: serde::de::DeserializeOwned + Sized,
+{
+    let result = serde_json::from_slice(&resp.body).map_err(BoticordError::Decode);
+    #[cfg(feature = "metrics")]
+    if let (Some(metrics), Err(e)) = (&client.metrics, &result) {
+        metrics.observe_error(route, e);
+    }
+    result
+}
+
+/// Response of a call, before decoding.
+pub(crate) struct Fetched {
+    response: HttpResponse,
+    latency: Duration,
+    from_cache: bool,
+    stale: bool,
+    stored_at: Option<SystemTime>,
+}
+
+async fn get_cached<T>(client: &BoticordClient, endpoint: Endpoint) -> Result<Cached<T>, BoticordError>
     where
         T: serde::de::DeserializeOwned + Sized,
 {
-    let resp = request(client, Method::GET, url, None::<()>).await?;
-    match resp.json().await {
-        Ok(data) => Ok(data),
-        Err(e) => Err(errors::from(e)),
+    let route = endpoint.route;
+    let fetched = fetch_get(client, endpoint).await?;
+    let value = decode(client, route, &fetched.response)?;
+    Ok(Cached { value, stale: fetched.stale, stored_at: fetched.stored_at })
+}
+
+async fn get_with_meta<T>(client: &BoticordClient, endpoint: Endpoint) -> Result<ApiResponse<T>, BoticordError>
+    where
+        T: serde::de::DeserializeOwned + Sized,
+{
+    let route = endpoint.route;
+    let fetched = fetch_get(client, endpoint).await?;
+    let value = decode(client, route, &fetched.response)?;
+    Ok(ApiResponse::new(value, fetched))
+}
+
+async fn fetch_get(client: &BoticordClient, endpoint: Endpoint) -> Result<Fetched, BoticordError> {
+    let started = Instant::now();
+    let (response, stale, stored_at) = match &client.cache {
+        Some(cache) if cache.ttl(endpoint.route).is_some() && !options::current().bypass_cache => {
+            cached_response(client, cache, endpoint).await?
+        }
+        _ => (request_get(client, endpoint).await?, false, None),
+    };
+    Ok(Fetched { response, latency: started.elapsed(), from_cache: stored_at.is_some(), stale, stored_at })
+}
+
+async fn cached_response(
+    client: &BoticordClient,
+    cache: &Cache,
+    endpoint: Endpoint,
+) -> Result<(HttpResponse, bool, Option<SystemTime>), BoticordError> {
+    match cache.lookup(&endpoint.url) {
+        Lookup::Fresh(hit) => Ok((hit.response, false, Some(hit.stored_at))),
+        Lookup::Stale { hit, refresh } => {
+            if refresh {
+                match tokio::runtime::Handle::try_current() {
+                    Ok(runtime) => {
+                        let (client, cache) = (client.clone(), cache.clone());
+                        let cached = hit.clone();
+                        // the refresh is not the caller's, so neither are the headers
+                        let endpoint = Endpoint { headers: HeaderMap::new(), ..endpoint };
+                        runtime.spawn(async move {
+                            let url = endpoint.url.clone();
+                            if fetch_into_cache(&client, &cache, endpoint, Some(cached)).await.is_err() {
+                                cache.refresh_failed(&url);
+                            }
+                        });
+                    }
+                    Err(_) => cache.refresh_failed(&endpoint.url),
+                }
+            }
+            Ok((hit.response, true, Some(hit.stored_at)))
+        }
+        Lookup::Expired(hit) => match fetch_into_cache(client, cache, endpoint, Some(hit.clone())).await {
+            Err(e) if cache.is_offline_mode() && is_offline_error(&e) => {
+                Ok((hit.response, true, Some(hit.stored_at)))
+            }
+            result => result.map(|resp| (resp, false, None)),
+        },
+        Lookup::Miss => fetch_into_cache(client, cache, endpoint, None).await.map(|resp| (resp, false, None)),
+    }
+}
+
+/// Fetches the response and caches it.
+///
+/// With a cached response the request is conditional,
+/// and the cached response is renewed if it is not modified.
+async fn fetch_into_cache(
+    client: &BoticordClient,
+    cache: &Cache,
+    mut endpoint: Endpoint,
+    cached: Option<Hit>,
+) -> Result<HttpResponse, BoticordError> {
+    let (url, route, id) = (endpoint.url.clone(), endpoint.route, endpoint.id.clone());
+    if let Some(cached) = &cached {
+        endpoint.headers.extend(cached.conditions());
+    }
+    let resp = request_get(client, endpoint).await?;
+    if let (StatusCode::NOT_MODIFIED, Some(cached)) = (resp.status, cached) {
+        cache.renew(&url);
+        return Ok(revalidated(cached.response, resp));
+    }
+    if serde_json::from_slice::<serde::de::IgnoredAny>(&resp.body).is_ok() {
+        cache.store(&url, route, id.as_deref(), &resp);
     }
+    Ok(resp)
 }
 
+/// The cached response with the status and headers of the `304` that revalidated it.
+///
+/// The headers describing the cached body are kept.
+fn revalidated(mut cached: HttpResponse, not_modified: HttpResponse) -> HttpResponse {
+    let mut name = None;
+    for (header, value) in not_modified.headers {
+        // only the first value of every header comes with its name
+        let first = header.is_some();
+        if first {
+            name = header;
+        }
+        match &name {
+            Some(name) if *name == CONTENT_TYPE || *name == CONTENT_LENGTH => {}
+            Some(name) if first => {
+                cached.headers.insert(name.clone(), value);
+            }
+            Some(name) => {
+                cached.headers.append(name.clone(), value);
+            }
+            None => {}
+        }
+    }
+    cached.status = not_modified.status;
+    cached
+}
+
+async fn request_get(client: &BoticordClient, endpoint: Endpoint) -> Result<HttpResponse, BoticordError> {
+    match &client.flights {
+        Some(flights) => {
+            // clients made with `for_token` share flights but not tokens,
+            // requests are only shared with the same conditions and retry policy
+            let retry = options::current().retry;
+            let key = format!("{:p} {} {:?} {:?}", Arc::as_ptr(&client.token), endpoint.url, endpoint.headers, retry);
+            // the shared request has no timeout or cancellation of the caller
+            // that leads it, every caller waits within its own instead
+            let shared = RequestOptions { retry, ..RequestOptions::default() };
+            let flight = flights.run(key, || options::scope(shared, request(client, endpoint, Method::GET, None::<()>)));
+            options::guard(flight).await
+        }
+        None => request(client, endpoint, Method::GET, None::<()>).await,
+    }
+}
 
-async fn post<T>(client: &BoticordClient,
-                 url: String,
-                 data: Option<T>) -> Result<(), BoticordError>
+/// Whether the error means BotiCord can't be reached right now.
+fn is_offline_error(e: &BoticordError) -> bool {
+    is_transport_error(e)
+        || matches!(e, BoticordError::Timeout(_))
+        || e.status().is_some_and(|s| s == StatusCode::TOO_MANY_REQUESTS || s.is_server_error())
+}
+
+async fn post_with_meta<T>(client: &BoticordClient,
+                           endpoint: Endpoint,
+                           data: Option<T>) -> Result<ApiResponse<()>, BoticordError>
     where
         T: serde::Serialize + Sized,
 {
-    request(client, Method::POST, url, data).await?;
-    Ok(())
+    let fetched = fetch(client, endpoint, Method::POST, data).await?;
+    Ok(ApiResponse::new((), fetched))
 }
 
-async fn post_with_response<T, R>(client: &BoticordClient,
-                    url: String,
-                    data: Option<T>) -> Result<R, BoticordError>
+async fn post_with_response_meta<T, R>(client: &BoticordClient,
+                                       endpoint: Endpoint,
+                                       data: Option<T>) -> Result<ApiResponse<R>, BoticordError>
     where
         T: serde::Serialize + Sized,
         R: serde::de::DeserializeOwned + Sized,
 {
-    let resp = request(client, Method::POST, url, data).await?;
-    match resp.json().await {
-        Ok(data) => Ok(data),
-        Err(e) => Err(errors::from(e)),
-    }
-}
\ No newline at end of file
+    let route = endpoint.route;
+    let fetched = fetch(client, endpoint, Method::POST, data).await?;
+    let value = decode(client, route, &fetched.response)?;
+    Ok(ApiResponse::new(value, fetched))
+}
+
+async fn fetch<T>(client: &BoticordClient,
+                  endpoint: Endpoint,
+                  method: Method,
+                  data: Option<T>) -> Result<Fetched, BoticordError>
+    where
+        T: serde::Serialize + Sized,
+{
+    let started = Instant::now();
+    let response = request(client, endpoint, method, data).await?;
+    Ok(Fetched { response, latency: started.elapsed(), from_cache: false, stale: false, stored_at: None })
+}
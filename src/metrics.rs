@@ -0,0 +1,189 @@
+//! Metrics of API calls and posted stats.
+//!
+//! Available with the `metrics` feature. Attach [`Metrics`] to a client with
+//! [`BoticordClient::with_metrics`] and every call is counted per route,
+//! timed, and its errors counted by kind. Gauges mirror the last
+//! successfully posted [`BotStats`].
+//!
+//! [`Metrics::render`] returns everything in the Prometheus text format,
+//! so a bot can serve it on its own `/metrics` route.
+//!
+//! ```no_run
+//! use boticordrs::metrics::Metrics;
+//! use boticordrs::BoticordClient;
+//!
+//! let metrics = Metrics::new();
+//! let client = BoticordClient::new("your token".to_string(), 2)
+//!     .expect("failed client")
+//!     .with_metrics(metrics.clone());
+//!
+//! // later, in the `/metrics` handler
+//! let body = metrics.render();
+//! ```
+//!
+//! [`BoticordClient::with_metrics`]: crate::BoticordClient::with_metrics
+
+use http::Method;
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::transport::HttpResponse;
+use crate::types::BotStats;
+use crate::BoticordError;
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+pub const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Shared registry of client metrics.
+///
+/// Clones share the same values, so one registry can be attached
+/// to several clients.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    registry: Arc<Mutex<Registry>>,
+}
+
+#[derive(Default)]
+struct Registry {
+    /// `(route, method, status)` → count, status is empty when no response was received.
+    requests: BTreeMap<(&'static str, String, String), u64>,
+    latency: BTreeMap<&'static str, Histogram>,
+    /// `(route, kind)` → count.
+    errors: BTreeMap<(&'static str, &'static str), u64>,
+    bot_stats: Option<BotStats>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Metrics {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Metrics::default()
+    }
+
+    /// Number of requests made to the route, e.g. `/bot/{}`.
+    pub fn requests(&self, route: &str) -> u64 {
+        self.registry().requests.iter()
+            .filter(|((r, _, _), _)| *r == route)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Number of errors of the kind (see [`BoticordError::kind`]) over all routes.
+    pub fn errors(&self, kind: &str) -> u64 {
+        self.registry().errors.iter()
+            .filter(|((_, k), _)| *k == kind)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Last successfully posted bot stats.
+    pub fn bot_stats(&self) -> Option<BotStats> {
+        self.registry().bot_stats.clone()
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let registry = self.registry();
+        let mut out = String::new();
+
+        out.push_str("# HELP boticord_requests_total BotiCord API requests.\n");
+        out.push_str("# TYPE boticord_requests_total counter\n");
+        for ((route, method, status), count) in &registry.requests {
+            let _ = writeln!(
+                out,
+                "boticord_requests_total{{route=\"{}\",method=\"{}\",status=\"{}\"}} {}",
+                route, method, status, count,
+            );
+        }
+
+        out.push_str("# HELP boticord_request_duration_seconds BotiCord API request latency, including retries.\n");
+        out.push_str("# TYPE boticord_request_duration_seconds histogram\n");
+        for (route, histogram) in &registry.latency {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "boticord_request_duration_seconds_bucket{{route=\"{}\",le=\"{}\"}} {}",
+                    route, bound, cumulative,
+                );
+            }
+            let _ = writeln!(
+                out,
+                "boticord_request_duration_seconds_bucket{{route=\"{}\",le=\"+Inf\"}} {}",
+                route, histogram.count,
+            );
+            let _ = writeln!(out, "boticord_request_duration_seconds_sum{{route=\"{}\"}} {}", route, histogram.sum);
+            let _ = writeln!(out, "boticord_request_duration_seconds_count{{route=\"{}\"}} {}", route, histogram.count);
+        }
+
+        out.push_str("# HELP boticord_errors_total Failed BotiCord API calls by error kind.\n");
+        out.push_str("# TYPE boticord_errors_total counter\n");
+        for ((route, kind), count) in &registry.errors {
+            let _ = writeln!(out, "boticord_errors_total{{route=\"{}\",kind=\"{}\"}} {}", route, kind, count);
+        }
+
+        if let Some(stats) = &registry.bot_stats {
+            for (name, help, value) in [
+                ("servers", "Servers in the last posted bot stats.", stats.servers),
+                ("shards", "Shards in the last posted bot stats.", stats.shards),
+                ("users", "Users in the last posted bot stats.", stats.users),
+            ] {
+                let _ = writeln!(out, "# HELP boticord_bot_{} {}", name, help);
+                let _ = writeln!(out, "# TYPE boticord_bot_{} gauge", name);
+                let _ = writeln!(out, "boticord_bot_{} {}", name, value);
+            }
+        }
+
+        out
+    }
+
+    pub(crate) fn observe(
+        &self,
+        route: &'static str,
+        method: &Method,
+        result: &Result<HttpResponse, BoticordError>,
+        latency: Duration,
+    ) {
+        let status = match result {
+            Ok(resp) => resp.status.as_u16().to_string(),
+            Err(e) => e.status().map(|s| s.as_u16().to_string()).unwrap_or_default(),
+        };
+
+        let mut registry = self.registry();
+        *registry.requests.entry((route, method.to_string(), status)).or_default() += 1;
+
+        let seconds = latency.as_secs_f64();
+        let histogram = registry.latency.entry(route).or_default();
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            histogram.buckets[bucket] += 1;
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+
+        if let Err(e) = result {
+            *registry.errors.entry((route, e.kind())).or_default() += 1;
+        }
+    }
+
+    pub(crate) fn observe_error(&self, route: &'static str, error: &BoticordError) {
+        *self.registry().errors.entry((route, error.kind())).or_default() += 1;
+    }
+
+    pub(crate) fn set_bot_stats(&self, stats: BotStats) {
+        self.registry().bot_stats = Some(stats);
+    }
+
+    fn registry(&self) -> std::sync::MutexGuard<'_, Registry> {
+        self.registry.lock().unwrap()
+    }
+}
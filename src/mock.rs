@@ -0,0 +1,664 @@
+//! In-process mock of BotiCord API for tests.
+//!
+//! Available with the `mock` feature. The server serves fixtures you seed it
+//! with, checks the token, can inject errors and latency and records every
+//! request, so you can assert on posted stats. Fixtures are served with
+//! an `ETag` and `If-None-Match` is answered with `304 Not Modified`.
+//!
+//! The same server is available as the `boticord-mock` binary for
+//! services written in other languages.
+//!
+//! ```
+//! use boticordrs::mock::{Failure, MockServer};
+//! use boticordrs::types::BotStats;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = MockServer::builder().token("secret").start().await.expect("failed server");
+//!     let client = server.client(2);
+//!
+//!     server.fail_next(Failure::status(502));
+//!     assert!(client.post_bot_stats(BotStats { servers: 1, shards: 1, users: 1 }).await.is_err());
+//!
+//!     client.post_bot_stats(BotStats { servers: 2, shards: 1, users: 10 }).await.expect("failed post");
+//!     assert_eq!(server.bot_stats()[0].servers, 2);
+//! }
+//! ```
+
+use hyper::header::{HeaderName, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_NONE_MATCH, RETRY_AFTER};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::store::{fnv1a, to_unix};
+use crate::token::Token;
+use crate::types::*;
+use crate::BoticordClient;
+
+/// Token used by [`MockServer::client`] when the server accepts any token.
+pub const DEFAULT_MOCK_TOKEN: &str = "mock-token";
+
+/// Error response the server returns instead of handling a request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Failure {
+    /// Response status.
+    pub status: u16,
+    /// Response body.
+    pub body: Value,
+    /// Value of the `Retry-After` header, in seconds.
+    pub retry_after: Option<u64>,
+}
+
+impl Failure {
+    /// Failure with the given status and a generic error body.
+    pub fn status(status: u16) -> Self {
+        Failure {
+            status,
+            body: error_body(status, "Injected failure"),
+            retry_after: None,
+        }
+    }
+
+    /// Replaces the response body.
+    pub fn with_body(mut self, body: Value) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Sets the `Retry-After` header.
+    pub fn with_retry_after(mut self, secs: u64) -> Self {
+        self.retry_after = Some(secs);
+        self
+    }
+}
+
+/// Scripted sequence of failures, usually loaded from a JSON file.
+///
+/// ```json
+/// {
+///     "steps": [
+///         { "route": "/stats", "status": 429, "retry_after": 1 },
+///         { "status": 500, "times": 3 }
+///     ]
+/// }
+/// ```
+///
+/// Requests that are not matched by any step are handled as usual,
+/// so the example fails the first stats post with 429, and then
+/// fails the next three requests of any kind with 500.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Scenario {
+    /// Steps in order of execution.
+    pub steps: Vec<ScenarioStep>,
+}
+
+/// Single step of a [`Scenario`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScenarioStep {
+    /// Route to fail (path without the API version). Any route if missing.
+    #[serde(default)]
+    pub route: Option<String>,
+    /// Response status.
+    pub status: u16,
+    /// Response body. Generic error body if missing.
+    #[serde(default)]
+    pub body: Option<Value>,
+    /// Value of the `Retry-After` header, in seconds.
+    #[serde(default)]
+    pub retry_after: Option<u64>,
+    /// How many requests to fail.
+    #[serde(default = "one")]
+    pub times: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+impl Scenario {
+    /// Loads a scenario from a JSON file.
+    pub fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let data = std::fs::read(path)?;
+        serde_json::from_slice(&data).map_err(std::io::Error::other)
+    }
+
+    fn failures(&self) -> impl Iterator<Item = (Option<String>, Failure)> + '_ {
+        self.steps.iter().flat_map(|step| {
+            let mut failure = Failure::status(step.status);
+            if let Some(body) = &step.body {
+                failure.body = body.clone();
+            }
+            failure.retry_after = step.retry_after;
+            std::iter::repeat_n((step.route.clone(), failure), step.times)
+        })
+    }
+}
+
+/// Request received by [`MockServer`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RecordedRequest {
+    /// HTTP method.
+    pub method: String,
+    /// Full request path, including the API version.
+    pub path: String,
+    /// JSON body, if any.
+    pub body: Option<Value>,
+}
+
+#[derive(Default)]
+struct State {
+    bind: Option<SocketAddr>,
+    token: Option<String>,
+    latency: Duration,
+    fixtures: HashMap<String, Value>,
+    links: Vec<ShortedLink>,
+    links_owner: Option<UserId>,
+    failures: VecDeque<(Option<String>, Failure)>,
+    requests: Vec<RecordedRequest>,
+    bot_stats: Vec<BotStats>,
+    server_stats: Vec<ServerStats>,
+}
+
+/// Builder for [`MockServer`].
+#[derive(Default)]
+pub struct MockServerBuilder {
+    state: State,
+}
+
+impl MockServerBuilder {
+    /// Listens on `addr` instead of a random local port.
+    pub fn bind(mut self, addr: SocketAddr) -> Self {
+        self.state.bind = Some(addr);
+        self
+    }
+
+    /// Requires requests to have this token, with the scheme of their API version.
+    /// Any token is accepted by default.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.state.token = Some(token.into());
+        self
+    }
+
+    /// Delays every response.
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.state.latency = latency;
+        self
+    }
+
+    /// Serves `value` on `GET` requests to `route`.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - Path without the API version, e.g. `/bot/123`,
+    ///   or with it, e.g. `/v1/bot/123`, to serve it for one version only
+    /// * `value` - Response body
+    ///
+    pub fn fixture(mut self, route: impl Into<String>, value: Value) -> Self {
+        self.state.fixtures.insert(route.into(), value);
+        self
+    }
+
+    /// Loads fixtures from a directory of JSON files.
+    ///
+    /// The file path becomes the route: `bot/123.json` is served on
+    /// `/bot/123`, `v1/bot/123/comments.json` on `/v1/bot/123/comments`.
+    /// A top-level `links.json` with an array of shorted links seeds
+    /// the link shortener instead.
+    pub fn fixtures_dir(mut self, dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let dir = dir.as_ref();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(current) = pending.pop() {
+            for entry in std::fs::read_dir(&current)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                if path.extension().is_none_or(|ext| ext != "json") {
+                    continue;
+                }
+
+                let data = std::fs::read(&path)?;
+                let value: Value = serde_json::from_slice(&data).map_err(|e| {
+                    std::io::Error::other(format!("{}: {}", path.display(), e))
+                })?;
+                let relative = path.strip_prefix(dir).expect("path is inside dir").with_extension("");
+                let route: Vec<_> = relative.iter().map(|p| p.to_string_lossy()).collect();
+                let route = format!("/{}", route.join("/"));
+
+                if route == "/links" {
+                    let links: Vec<ShortedLink> = serde_json::from_value(value).map_err(std::io::Error::other)?;
+                    self.state.links.extend(links);
+                } else {
+                    self.state.fixtures.insert(route, value);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Queues failures of the scenario.
+    pub fn scenario(mut self, scenario: &Scenario) -> Self {
+        self.state.failures.extend(scenario.failures());
+        self
+    }
+
+    /// Serves the bot by its id and short code.
+    pub fn bot(self, bot: &Bot) -> Self {
+        let code = bot.short_code.clone();
+        let this = self.typed(format!("/bot/{}", bot.id.0), bot);
+        match code {
+            Some(code) => this.typed(format!("/bot/{}", code), bot),
+            None => this,
+        }
+    }
+
+    /// Serves the server by its id and short code.
+    pub fn server(self, server: &Server) -> Self {
+        let code = server.short_code.clone();
+        let this = self.typed(format!("/server/{}", server.id.0), server);
+        match code {
+            Some(code) => this.typed(format!("/server/{}", code), server),
+            None => this,
+        }
+    }
+
+    /// Serves the user's profile.
+    pub fn user(self, user: &UserInformation) -> Self {
+        self.typed(format!("/profile/{}", user.id.0), user)
+    }
+
+    /// Serves comments of the bot.
+    pub fn bot_comments(self, bot: &BotId, comments: &[SingleComment]) -> Self {
+        self.typed(format!("/bot/{}/comments", bot.0), &comments)
+    }
+
+    /// Serves comments of the server.
+    pub fn server_comments(self, server: &ServerId, comments: &[SingleComment]) -> Self {
+        self.typed(format!("/server/{}/comments", server.0), &comments)
+    }
+
+    /// Serves comments written by the user.
+    pub fn user_comments(self, user: &UserId, comments: &UserComments) -> Self {
+        self.typed(format!("/profile/{}/comments", user.0), comments)
+    }
+
+    /// Serves bots of the user.
+    pub fn user_bots(self, user: &UserId, bots: &[SingleUserBot]) -> Self {
+        self.typed(format!("/bots/{}", user.0), &bots)
+    }
+
+    /// Adds a shorted link.
+    pub fn shorted_link(mut self, link: ShortedLink) -> Self {
+        self.state.links.push(link);
+        self
+    }
+
+    /// Owner of links created through the server.
+    pub fn links_owner(mut self, owner: UserId) -> Self {
+        self.state.links_owner = Some(owner);
+        self
+    }
+
+    fn typed<T: Serialize>(self, route: String, value: &T) -> Self {
+        let value = serde_json::to_value(value).expect("models always serialize");
+        self.fixture(route, value)
+    }
+
+    /// Starts the server on a random local port.
+    ///
+    /// Must be called inside a tokio runtime. The server stops when
+    /// [`MockServer`] is dropped.
+    pub async fn start(self) -> std::io::Result<MockServer> {
+        let bind = self.state.bind.unwrap_or_else(|| SocketAddr::from(([127, 0, 0, 1], 0)));
+        let listener = TcpListener::bind(bind)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let state = Arc::new(Mutex::new(self.state));
+        let service_state = Arc::clone(&state);
+        let make_service = make_service_fn(move |_| {
+            let state = Arc::clone(&service_state);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| handle(Arc::clone(&state), req)))
+            }
+        });
+
+        let (shutdown, rx) = oneshot::channel::<()>();
+        let server = hyper::Server::from_tcp(listener)
+            .map_err(std::io::Error::other)?
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                let _ = rx.await;
+            });
+        tokio::spawn(server);
+
+        Ok(MockServer { addr, state, shutdown: Some(shutdown) })
+    }
+}
+
+/// Running mock server.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Constructs a new builder.
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder::default()
+    }
+
+    /// Address the server listens on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL for [`BoticordClient::with_base_url`].
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Constructs a client pointed at the server, using its token.
+    pub fn client(&self, version: u64) -> BoticordClient {
+        let token = self
+            .state
+            .lock()
+            .unwrap()
+            .token
+            .clone()
+            .unwrap_or_else(|| DEFAULT_MOCK_TOKEN.to_string());
+        BoticordClient::new(token, version)
+            .expect("failed client")
+            .with_base_url(self.url())
+    }
+
+    /// Changes the delay of every response.
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.lock().unwrap().latency = latency;
+    }
+
+    /// Serves `value` on `GET` requests to `route`.
+    pub fn set_fixture(&self, route: impl Into<String>, value: Value) {
+        self.state.lock().unwrap().fixtures.insert(route.into(), value);
+    }
+
+    /// Fails the next request with `failure`.
+    ///
+    /// Failures are queued and applied in order, so calling it several times
+    /// fails several requests.
+    pub fn fail_next(&self, failure: Failure) {
+        self.state.lock().unwrap().failures.push_back((None, failure));
+    }
+
+    /// Fails the next request to `route` (path without the API version).
+    pub fn fail_route(&self, route: impl Into<String>, failure: Failure) {
+        self.state.lock().unwrap().failures.push_back((Some(route.into()), failure));
+    }
+
+    /// Queues failures of the scenario.
+    pub fn load_scenario(&self, scenario: &Scenario) {
+        self.state.lock().unwrap().failures.extend(scenario.failures());
+    }
+
+    /// Returns all received requests.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Returns all successfully posted bot stats.
+    pub fn bot_stats(&self) -> Vec<BotStats> {
+        self.state.lock().unwrap().bot_stats.clone()
+    }
+
+    /// Returns all successfully posted server stats.
+    pub fn server_stats(&self) -> Vec<ServerStats> {
+        self.state.lock().unwrap().server_stats.clone()
+    }
+
+    /// Forgets recorded requests and stats, and drops pending failures.
+    pub fn reset(&self) {
+        self.state.lock().unwrap().reset();
+    }
+}
+
+impl State {
+    fn reset(&mut self) {
+        self.failures.clear();
+        self.requests.clear();
+        self.bot_stats.clear();
+        self.server_stats.clear();
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+fn error_body(status: u16, message: &str) -> Value {
+    json!({ "error": { "code": status, "message": message } })
+}
+
+fn json_response(status: u16, body: &Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("valid response")
+}
+
+/// Fixture response with an `ETag`, `304` when the tag matches `If-None-Match`.
+fn tagged_response(body: &Value, if_none_match: Option<&str>) -> Response<Body> {
+    let body = body.to_string();
+    let etag = format!("\"{:016x}\"", fnv1a(body.as_bytes()));
+    let resp = Response::builder().header(ETAG, etag.as_str());
+    if if_none_match == Some(etag.as_str()) {
+        return resp.status(304).body(Body::empty()).expect("valid response");
+    }
+    resp.status(200)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("valid response")
+}
+
+fn error_response(status: u16, message: &str) -> Response<Body> {
+    json_response(status, &error_body(status, message))
+}
+
+fn failure_response(failure: &Failure) -> Response<Body> {
+    let mut resp = json_response(failure.status, &failure.body);
+    if let Some(secs) = failure.retry_after {
+        resp.headers_mut().insert(RETRY_AFTER, secs.into());
+    }
+    resp
+}
+
+/// API version from the `/v{n}` prefix of the path.
+fn version_of(path: &str) -> Option<u64> {
+    let rest = path.strip_prefix("/v")?;
+    rest[..rest.find('/')?].parse().ok()
+}
+
+/// Strips the `/v{n}` prefix from the path.
+pub(crate) fn route_of(path: &str) -> Option<&str> {
+    let rest = path.strip_prefix("/v")?;
+    let slash = rest.find('/')?;
+    rest[..slash].parse::<u64>().ok()?;
+    Some(&rest[slash..])
+}
+
+async fn handle(state: Arc<Mutex<State>>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let header = |name: HeaderName| req.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let token = header(AUTHORIZATION);
+    let if_none_match = header(IF_NONE_MATCH);
+    let bytes = hyper::body::to_bytes(req.into_body()).await.unwrap_or_default();
+    let body: Option<Value> = serde_json::from_slice(&bytes).ok();
+
+    let latency = state.lock().unwrap().latency;
+    if !latency.is_zero() {
+        tokio::time::sleep(latency).await;
+    }
+
+    let mut state = state.lock().unwrap();
+    if let Some(route) = path.strip_prefix(ADMIN_PREFIX) {
+        return Ok(admin(&mut state, &method, route, body));
+    }
+
+    state.requests.push(RecordedRequest {
+        method: method.to_string(),
+        path: path.clone(),
+        body: body.clone(),
+    });
+
+    let route = match route_of(&path) {
+        Some(route) => route.to_string(),
+        None => return Ok(error_response(404, "Not found")),
+    };
+
+    // Failures are strictly ordered, the next one waits for a matching request.
+    let failing = state
+        .failures
+        .front()
+        .is_some_and(|(r, _)| r.as_ref().is_none_or(|r| *r == route));
+    if failing {
+        let (_, failure) = state.failures.pop_front().expect("failure exists");
+        return Ok(failure_response(&failure));
+    }
+
+    if let Some(expected) = &state.token {
+        let expected = Token::new(expected.as_str()).authorization(version_of(&path).unwrap_or(1)).ok();
+        if expected.is_none() || token != expected {
+            return Ok(error_response(401, "Unauthorized"));
+        }
+    }
+
+    Ok(dispatch(&mut state, &method, &path, &route, body, if_none_match))
+}
+
+/// Prefix of the admin routes.
+///
+/// * `GET /__admin/requests` - recorded requests
+/// * `GET /__admin/stats` - posted bot and server stats
+/// * `POST /__admin/reset` - same as [`MockServer::reset`]
+/// * `POST /__admin/scenario` - queues failures of the [`Scenario`] in the body
+pub const ADMIN_PREFIX: &str = "/__admin";
+
+fn admin(state: &mut State, method: &Method, route: &str, body: Option<Value>) -> Response<Body> {
+    match (method, route) {
+        (&Method::GET, "/requests") => json_response(200, &json!(state.requests)),
+        (&Method::GET, "/stats") => json_response(200, &json!({
+            "bots": state.bot_stats,
+            "servers": state.server_stats,
+        })),
+        (&Method::POST, "/reset") => {
+            state.reset();
+            json_response(200, &json!({ "ok": true }))
+        }
+        (&Method::POST, "/scenario") => {
+            match serde_json::from_value::<Scenario>(body.unwrap_or(Value::Null)) {
+                Ok(scenario) => {
+                    state.failures.extend(scenario.failures());
+                    json_response(200, &json!({ "ok": true }))
+                }
+                Err(e) => error_response(400, &e.to_string()),
+            }
+        }
+        _ => error_response(404, "Not found"),
+    }
+}
+
+fn dispatch(
+    state: &mut State,
+    method: &Method,
+    path: &str,
+    route: &str,
+    body: Option<Value>,
+    if_none_match: Option<String>,
+) -> Response<Body> {
+    let body = body.unwrap_or(Value::Null);
+    match (method, route) {
+        (&Method::GET, _) => match state.fixtures.get(path).or_else(|| state.fixtures.get(route)) {
+            Some(value) => tagged_response(value, if_none_match.as_deref()),
+            None => error_response(404, "Not found"),
+        },
+        (&Method::POST, "/stats") => match serde_json::from_value::<BotStats>(body) {
+            Ok(stats) => {
+                state.bot_stats.push(stats);
+                json_response(200, &json!({ "ok": true }))
+            }
+            Err(e) => error_response(400, &e.to_string()),
+        },
+        (&Method::POST, "/server") => match serde_json::from_value::<ServerStats>(body) {
+            Ok(stats) => {
+                state.server_stats.push(stats);
+                json_response(200, &json!({ "ok": true }))
+            }
+            Err(e) => error_response(400, &e.to_string()),
+        },
+        (&Method::POST, "/links/get") => {
+            let code = body.get("code").and_then(Value::as_str);
+            let links: Vec<&ShortedLink> = state
+                .links
+                .iter()
+                .filter(|l| code.is_none_or(|c| l.code == c))
+                .collect();
+            json_response(200, &json!(links))
+        }
+        (&Method::POST, "/links/create") => match serde_json::from_value::<ShortenerBody>(body) {
+            Ok(ShortenerBody { link: Some(link), code, domain }) => {
+                let id = state.links.iter().map(|l| l.id).max().unwrap_or_default() + 1;
+                let code = code.unwrap_or_else(|| format!("l{}", id));
+                let domain = domain.unwrap_or(LinkDomain::BCordCC).host().to_string();
+                if state.links.iter().any(|l| l.code == code && l.domain == domain) {
+                    return error_response(409, "Code is already taken");
+                }
+                let created = ShortedLink {
+                    id,
+                    code,
+                    owner_id: state.links_owner.clone().unwrap_or_else(|| UserId("0".to_string())),
+                    domain,
+                    views: 0,
+                    link,
+                    date: to_unix(SystemTime::now()),
+                };
+                state.links.push(created.clone());
+                json_response(200, &json!(created))
+            }
+            Ok(_) => error_response(400, "Link is required"),
+            Err(e) => error_response(400, &e.to_string()),
+        },
+        (&Method::POST, "/links/delete") => match serde_json::from_value::<ShortenerBody>(body) {
+            Ok(ShortenerBody { code: Some(code), domain, .. }) => {
+                let domain = domain.map(|d| d.host());
+                let before = state.links.len();
+                state
+                    .links
+                    .retain(|l| l.code != code || domain.is_some_and(|d| l.domain != d));
+                if state.links.len() == before {
+                    error_response(404, "Not found")
+                } else {
+                    json_response(200, &json!({ "ok": true }))
+                }
+            }
+            Ok(_) => error_response(400, "Code is required"),
+            Err(e) => error_response(400, &e.to_string()),
+        },
+        _ => error_response(404, "Not found"),
+    }
+}
@@ -0,0 +1,129 @@
+//! Options of individual calls.
+//!
+//! [`BoticordClient::with_options`] makes the calls awaited in its closure
+//! with the given [`RequestOptions`], so they apply to every method and its
+//! `_cached` and `_with_meta` variants alike. Other calls of the client,
+//! including the background refreshes of the [cache](crate::cache), keep
+//! the client's defaults.
+//!
+//! ```no_run
+//! use boticordrs::options::RequestOptions;
+//! use boticordrs::types::BotStats;
+//! use boticordrs::BoticordClient;
+//! use http::{HeaderName, HeaderValue};
+//!
+//! use std::time::Duration;
+//!
+//! # async fn run(client: BoticordClient) -> Result<(), boticordrs::BoticordError> {
+//! let options = RequestOptions::new()
+//!     .timeout(Duration::from_secs(2))
+//!     .header(HeaderName::from_static("x-correlation-id"), HeaderValue::from_static("stats-42"));
+//! let stats = BotStats { servers: 2514, shards: 3, users: 338250 };
+//! client.with_options(options, |client| client.post_bot_stats(stats)).await?;
+//!
+//! // other calls keep the client's defaults
+//! client.get_user_comments("585766846268047370".to_string()).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`BoticordClient::with_options`]: crate::BoticordClient::with_options
+
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+
+use std::future::Future;
+use std::time::Duration;
+
+pub use tokio_util::sync::CancellationToken;
+
+use crate::retry::RetryPolicy;
+use crate::BoticordError;
+
+/// Options of the calls made by a client, see the [module docs](self).
+#[derive(Clone, Debug, Default)]
+pub struct RequestOptions {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) headers: HeaderMap,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) bypass_cache: bool,
+    pub(crate) cancel: Option<CancellationToken>,
+}
+
+impl RequestOptions {
+    /// Options that change nothing.
+    pub fn new() -> Self {
+        RequestOptions::default()
+    }
+
+    /// Fails the call with [`BoticordError::Timeout`] if it takes longer,
+    /// retries included.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Adds a header to the requests.
+    ///
+    /// `Authorization` and `Content-Type` are always set by the client.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Retries with this policy instead of the client's.
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Skips the cache, the response is not cached either.
+    pub fn bypass_cache(mut self, bypass: bool) -> Self {
+        self.bypass_cache = bypass;
+        self
+    }
+
+    /// Fails the call with [`BoticordError::Cancelled`] once `token` is cancelled.
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+}
+
+tokio::task_local! {
+    static CURRENT: RequestOptions;
+}
+
+/// Runs `call` with the options, tasks it spawns don't inherit them.
+pub(crate) async fn scope<F: Future>(options: RequestOptions, call: F) -> F::Output {
+    CURRENT.scope(options, call).await
+}
+
+/// Options of the current call, the defaults outside of a scope.
+pub(crate) fn current() -> RequestOptions {
+    CURRENT.try_with(RequestOptions::clone).unwrap_or_default()
+}
+
+/// Runs the call within the timeout of the current options, until it is cancelled.
+pub(crate) async fn guard<T, F>(call: F) -> Result<T, BoticordError>
+    where
+        F: Future<Output = Result<T, BoticordError>>,
+{
+    let RequestOptions { timeout, cancel, .. } = current();
+    let call = async {
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, call)
+                .await
+                .unwrap_or(Err(BoticordError::Timeout(timeout))),
+            None => call.await,
+        }
+    };
+    match cancel {
+        Some(token) => tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(BoticordError::Cancelled),
+            result = call => result,
+        },
+        None => call.await,
+    }
+}
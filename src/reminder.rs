@@ -0,0 +1,250 @@
+//! Bump reminders.
+//!
+//! [`ReminderScheduler`] remembers when users asked to be reminded about
+//! the next bump of a server and calls your callback when the time comes.
+//! Reminders are kept in a [`ReminderStore`], so they survive restarts.
+//!
+//! A reminder scheduled with [`ReminderScheduler::schedule_server`] keeps the
+//! bump count of the server, so the callback can tell with
+//! [`Reminder::bumped_since`] whether someone else bumped it in the meantime.
+//!
+//! ```no_run
+//! use boticordrs::reminder::ReminderScheduler;
+//! use boticordrs::store::JsonFileStore;
+//! use boticordrs::types::{ServerId, UserId};
+//!
+//! use std::time::{Duration, SystemTime};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let store = JsonFileStore::new("reminders.json");
+//!     let scheduler = ReminderScheduler::new(store).expect("failed to load reminders");
+//!
+//!     scheduler.schedule(
+//!         UserId("585766846268047370".to_string()),
+//!         ServerId("724663360934772797".to_string()),
+//!         SystemTime::now() + Duration::from_secs(4 * 60 * 60),
+//!     ).expect("failed to save reminder");
+//!
+//!     scheduler.run(|reminder| async move {
+//!         println!("<@{}>, you can bump the server again!", reminder.user.0);
+//!     }).await.expect("failed to save reminders");
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::bump::{BumpManager, BumpStore};
+use crate::store::{from_unix, to_unix, JsonFileStore, MemoryStore};
+use crate::types::{Server, ServerId, UserId};
+use crate::BoticordError;
+
+/// Single reminder.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Reminder {
+    /// User to remind.
+    pub user: UserId,
+    /// Server to bump.
+    pub server: ServerId,
+    /// Unix timestamp (seconds) when the reminder is due.
+    pub due_at: u64,
+    /// Bump count of the server when the reminder was scheduled, if known.
+    #[serde(default)]
+    pub bumps: Option<u64>,
+}
+
+impl Reminder {
+    /// Time when the reminder is due.
+    pub fn due_time(&self) -> SystemTime {
+        from_unix(self.due_at)
+    }
+
+    /// Whether the server was bumped since the reminder was scheduled,
+    /// given its current bump count. `false` if the count wasn't known.
+    pub fn bumped_since(&self, bumps: u64) -> bool {
+        self.bumps.is_some_and(|seen| bumps > seen)
+    }
+}
+
+/// Storage for [`ReminderScheduler`] state.
+pub trait ReminderStore: Send + Sync {
+    /// Loads all saved reminders.
+    fn load(&self) -> Result<Vec<Reminder>, BoticordError>;
+
+    /// Replaces saved reminders with the given ones.
+    fn save(&self, reminders: &[Reminder]) -> Result<(), BoticordError>;
+}
+
+impl ReminderStore for MemoryStore {
+    fn load(&self) -> Result<Vec<Reminder>, BoticordError> {
+        Ok(Vec::new())
+    }
+
+    fn save(&self, _reminders: &[Reminder]) -> Result<(), BoticordError> {
+        Ok(())
+    }
+}
+
+impl ReminderStore for JsonFileStore<Vec<Reminder>> {
+    fn load(&self) -> Result<Vec<Reminder>, BoticordError> {
+        JsonFileStore::load(self)
+    }
+
+    fn save(&self, reminders: &[Reminder]) -> Result<(), BoticordError> {
+        JsonFileStore::save(self, reminders)
+    }
+}
+
+struct Inner<S> {
+    store: S,
+    reminders: Mutex<Vec<Reminder>>,
+    notify: Notify,
+}
+
+/// Schedules bump reminders and fires them when they are due.
+///
+/// Cloning is cheap, all clones share the same reminders.
+pub struct ReminderScheduler<S = MemoryStore> {
+    inner: Arc<Inner<S>>,
+}
+
+impl<S> Clone for ReminderScheduler<S> {
+    fn clone(&self) -> Self {
+        ReminderScheduler { inner: Arc::clone(&self.inner) }
+    }
+}
+
+impl<S: ReminderStore> ReminderScheduler<S> {
+    /// Constructs a new scheduler, loading reminders from `store`.
+    pub fn new(store: S) -> Result<Self, BoticordError> {
+        let reminders = store.load()?;
+        Ok(ReminderScheduler {
+            inner: Arc::new(Inner {
+                store,
+                reminders: Mutex::new(reminders),
+                notify: Notify::new(),
+            }),
+        })
+    }
+
+    /// Schedules a reminder, replacing the previous one for the same user and server.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - User to remind
+    /// * `server` - Server to bump
+    /// * `at` - When to remind
+    ///
+    pub fn schedule(&self, user: UserId, server: ServerId, at: SystemTime) -> Result<(), BoticordError> {
+        self.insert(Reminder { user, server, due_at: to_unix(at), bumps: None })
+    }
+
+    /// Same as [`schedule`](Self::schedule), remembering the bump count of the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - User to remind
+    /// * `server` - Server to bump, as returned by [`get_server_info`](crate::BoticordClient::get_server_info)
+    /// * `at` - When to remind
+    ///
+    pub fn schedule_server(&self, user: UserId, server: &Server, at: SystemTime) -> Result<(), BoticordError> {
+        self.insert(Reminder {
+            user,
+            server: server.id.clone(),
+            due_at: to_unix(at),
+            bumps: Some(server.information.bumps),
+        })
+    }
+
+    fn insert(&self, reminder: Reminder) -> Result<(), BoticordError> {
+        self.update(|reminders| {
+            reminders.retain(|r| r.user != reminder.user || r.server != reminder.server);
+            reminders.push(reminder);
+        })
+    }
+
+    /// Schedules a reminder for the next bump known by `manager`.
+    ///
+    /// Returns the time of the reminder, or `None` if the server
+    /// can be bumped right now and nothing was scheduled.
+    pub async fn schedule_next_bump<B: BumpStore>(
+        &self,
+        manager: &BumpManager<B>,
+        user: UserId,
+        server: ServerId,
+    ) -> Result<Option<SystemTime>, BoticordError> {
+        match manager.next_bump_at(&server).await {
+            Some(at) => {
+                self.schedule(user, server, at)?;
+                Ok(Some(at))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Cancels the reminder for the user and server.
+    pub fn cancel(&self, user: &UserId, server: &ServerId) -> Result<(), BoticordError> {
+        self.update(|reminders| reminders.retain(|r| &r.user != user || &r.server != server))
+    }
+
+    /// Returns all pending reminders.
+    pub fn pending(&self) -> Vec<Reminder> {
+        self.inner.reminders.lock().unwrap().clone()
+    }
+
+    /// Fires reminders as they become due. Never returns unless saving fails.
+    ///
+    /// Reminders that were due while the bot was offline are fired immediately.
+    /// A reminder is removed only after its callback has finished, so one that
+    /// couldn't be removed from the store fires again after a restart.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Called for every due reminder
+    ///
+    pub async fn run<F, Fut>(&self, mut callback: F) -> Result<(), BoticordError>
+        where
+            F: FnMut(Reminder) -> Fut,
+            Fut: Future<Output = ()>,
+    {
+        loop {
+            let notified = self.inner.notify.notified();
+            let now = to_unix(SystemTime::now());
+
+            let due: Vec<_> = self.pending().into_iter().filter(|r| r.due_at <= now).collect();
+            for reminder in &due {
+                callback(reminder.clone()).await;
+            }
+            // reminders rescheduled by the callbacks are kept
+            if !due.is_empty() {
+                self.update(|reminders| reminders.retain(|r| !due.contains(r)))?;
+            }
+            let next = self.inner.reminders.lock().unwrap().iter().map(|r| r.due_at).min();
+
+            match next {
+                Some(at) => {
+                    let wait = from_unix(at).duration_since(SystemTime::now()).unwrap_or_default();
+                    let _ = tokio::time::timeout(wait, notified).await;
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    /// Changes the reminders, keeping them as they were if saving fails.
+    fn update(&self, f: impl FnOnce(&mut Vec<Reminder>)) -> Result<(), BoticordError> {
+        let mut reminders = self.inner.reminders.lock().unwrap();
+        let mut updated = reminders.clone();
+        f(&mut updated);
+        self.inner.store.save(&updated)?;
+        *reminders = updated;
+        drop(reminders);
+        self.inner.notify.notify_one();
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+//! Responses together with their metadata.
+//!
+//! Every method of [`BoticordClient`] has a `*_with_meta` variant that
+//! returns an [`ApiResponse`]: the decoded body along with the status,
+//! headers, rate-limit state, request ID and latency of the call.
+//!
+//! ```no_run
+//! use boticordrs::BoticordClient;
+//!
+//! # async fn run() -> Result<(), boticordrs::BoticordError> {
+//! let client = BoticordClient::new("your token", 2)?;
+//! let bot = client.get_bot_info_with_meta("724663360934772797".to_string()).await?;
+//!
+//! if let Some(limit) = bot.rate_limit {
+//!     println!("{:?} requests left until {:?}", limit.remaining, limit.reset);
+//! }
+//! println!("{} took {:?}", bot.value.short_code.unwrap_or_default(), bot.latency);
+//! # Ok(())
+//! # }
+//! ```
+
+use http::{HeaderMap, Method, StatusCode};
+
+use std::time::{Duration, SystemTime};
+
+use crate::store::from_unix;
+use crate::types::*;
+use crate::transport::HttpResponse;
+use crate::{
+    decode, fetch, get_with_meta, post_with_meta, post_with_response_meta, raw_endpoint, BoticordClient,
+    BoticordError, Endpoint, Fetched,
+};
+
+/// Headers that may carry the ID of the request, in order of preference.
+const REQUEST_ID_HEADERS: [&str; 3] = ["x-request-id", "request-id", "cf-ray"];
+
+/// Decoded body of a response with its metadata.
+#[derive(Clone, Debug)]
+pub struct ApiResponse<T> {
+    /// Decoded body.
+    pub value: T,
+    /// Response status, `304` when a cached response was revalidated.
+    pub status: StatusCode,
+    /// Response headers, those of the `304` merged into the cached ones
+    /// when a cached response was revalidated.
+    pub headers: HeaderMap,
+    /// Rate limit state reported by the response, if any.
+    pub rate_limit: Option<RateLimit>,
+    /// ID of the request assigned by BotiCord or its proxy, if any.
+    pub request_id: Option<String>,
+    /// Time the call took, including retries.
+    pub latency: Duration,
+    /// Whether the response came from the [cache](crate::cache).
+    pub from_cache: bool,
+    /// Whether the cached response is older than the TTL of its route.
+    pub stale: bool,
+}
+
+/// Rate limit state from the `X-RateLimit-*` or `RateLimit-*` headers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimit {
+    /// Requests allowed in the window.
+    pub limit: Option<u64>,
+    /// Requests left in the window.
+    pub remaining: Option<u64>,
+    /// When the window resets.
+    pub reset: Option<SystemTime>,
+}
+
+impl<T> ApiResponse<T> {
+    pub(crate) fn new(value: T, fetched: Fetched) -> Self {
+        let headers = fetched.response.headers;
+        ApiResponse {
+            value,
+            status: fetched.response.status,
+            rate_limit: RateLimit::from_headers(&headers),
+            request_id: request_id(&headers),
+            headers,
+            latency: fetched.latency,
+            from_cache: fetched.from_cache,
+            stale: fetched.stale,
+        }
+    }
+
+    /// Replaces the body, keeping the metadata.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ApiResponse<U> {
+        ApiResponse {
+            value: f(self.value),
+            status: self.status,
+            headers: self.headers,
+            rate_limit: self.rate_limit,
+            request_id: self.request_id,
+            latency: self.latency,
+            from_cache: self.from_cache,
+            stale: self.stale,
+        }
+    }
+}
+
+/// ID of the request from the first of [`REQUEST_ID_HEADERS`] present.
+pub(crate) fn request_id(headers: &HeaderMap) -> Option<String> {
+    REQUEST_ID_HEADERS
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok())
+        .map(str::to_string)
+}
+
+impl RateLimit {
+    /// Reads the rate limit state, `None` if the headers say nothing about it.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let number = |name: &str| -> Option<u64> {
+            ["x-ratelimit-", "ratelimit-"]
+                .iter()
+                .find_map(|prefix| headers.get(format!("{}{}", prefix, name)))
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .map(|v| v.max(0.0).ceil() as u64)
+        };
+        let limit = number("limit");
+        let remaining = number("remaining");
+        // a delay in seconds, or a unix timestamp for large values
+        let reset = number("reset").map(|reset| {
+            if reset > 1_000_000_000 {
+                from_unix(reset)
+            } else {
+                SystemTime::now() + Duration::from_secs(reset)
+            }
+        });
+
+        if limit.is_none() && remaining.is_none() && reset.is_none() {
+            None
+        } else {
+            Some(RateLimit { limit, remaining, reset })
+        }
+    }
+}
+
+impl BoticordClient {
+    /// Same as [`get_bot_info`](Self::get_bot_info), with metadata.
+    pub async fn get_bot_info_with_meta(&self, bot: String) -> Result<ApiResponse<Bot>, BoticordError> {
+        let endpoint = endpoint!(self, "get_bot_info", "/bot/{}", bot);
+        get_with_meta(self, endpoint).await
+    }
+
+    /// Same as [`get_server_info`](Self::get_server_info), with metadata.
+    pub async fn get_server_info_with_meta(&self, server: String) -> Result<ApiResponse<Server>, BoticordError> {
+        let endpoint = endpoint!(self, "get_server_info", "/server/{}", server);
+        get_with_meta(self, endpoint).await
+    }
+
+    /// Same as [`get_user_info`](Self::get_user_info), with metadata.
+    pub async fn get_user_info_with_meta(
+        &self,
+        user: String,
+    ) -> Result<ApiResponse<UserInformation>, BoticordError> {
+        let endpoint = endpoint!(self, "get_user_info", "/profile/{}", user);
+        get_with_meta(self, endpoint).await
+    }
+
+    /// Same as [`get_bot_comments`](Self::get_bot_comments), with metadata.
+    pub async fn get_bot_comments_with_meta(
+        &self,
+        bot: String,
+    ) -> Result<ApiResponse<Vec<SingleComment>>, BoticordError> {
+        let endpoint = endpoint!(self, "get_bot_comments", "/bot/{}/comments", bot);
+        get_with_meta(self, endpoint).await
+    }
+
+    /// Same as [`get_server_comments`](Self::get_server_comments), with metadata.
+    pub async fn get_server_comments_with_meta(
+        &self,
+        server: String,
+    ) -> Result<ApiResponse<Vec<SingleComment>>, BoticordError> {
+        let endpoint = endpoint!(self, "get_server_comments", "/server/{}/comments", server);
+        get_with_meta(self, endpoint).await
+    }
+
+    /// Same as [`get_user_comments`](Self::get_user_comments), with metadata.
+    pub async fn get_user_comments_with_meta(
+        &self,
+        user: String,
+    ) -> Result<ApiResponse<UserComments>, BoticordError> {
+        let endpoint = endpoint!(self, "get_user_comments", "/profile/{}/comments", user);
+        get_with_meta(self, endpoint).await
+    }
+
+    /// Same as [`get_user_bots`](Self::get_user_bots), with metadata.
+    pub async fn get_user_bots_with_meta(
+        &self,
+        user: String,
+    ) -> Result<ApiResponse<Vec<SingleUserBot>>, BoticordError> {
+        let endpoint = endpoint!(self, "get_user_bots", "/bots/{}", user);
+        get_with_meta(self, endpoint).await
+    }
+
+    /// Same as [`get_my_shorted_links`](Self::get_my_shorted_links), with metadata.
+    pub async fn get_my_shorted_links_with_meta(&self) -> Result<ApiResponse<Vec<ShortedLink>>, BoticordError> {
+        let endpoint = endpoint!(self, "get_my_shorted_links", "/links/get");
+        post_with_response_meta(self, endpoint, Some(EmptyBody {})).await
+    }
+
+    /// Same as [`search_for_shorted_link`](Self::search_for_shorted_link), with metadata.
+    pub async fn search_for_shorted_link_with_meta(
+        &self,
+        shortener_body: ShortenerBody,
+    ) -> Result<ApiResponse<Vec<ShortedLink>>, BoticordError> {
+        let endpoint = endpoint!(self, "search_for_shorted_link", "/links/get");
+        post_with_response_meta(self, endpoint, Some(shortener_body)).await
+    }
+
+    /// Same as [`create_shorted_link`](Self::create_shorted_link), with metadata.
+    pub async fn create_shorted_link_with_meta(
+        &self,
+        shortener_body: ShortenerBody,
+    ) -> Result<ApiResponse<ShortedLink>, BoticordError> {
+        let endpoint = endpoint!(self, "create_shorted_link", "/links/create");
+        post_with_response_meta(self, endpoint, Some(shortener_body)).await
+    }
+
+    /// Same as [`delete_shorted_link`](Self::delete_shorted_link), with metadata.
+    pub async fn delete_shorted_link_with_meta(
+        &self,
+        shortener_body: ShortenerBody,
+    ) -> Result<ApiResponse<()>, BoticordError> {
+        let endpoint = endpoint!(self, "delete_shorted_link", "/links/delete");
+        post_with_meta(self, endpoint, Some(shortener_body)).await
+    }
+
+    /// Same as [`post_bot_stats`](Self::post_bot_stats), with metadata.
+    pub async fn post_bot_stats_with_meta(&self, stats: BotStats) -> Result<ApiResponse<()>, BoticordError> {
+        let endpoint = endpoint!(self, "post_bot_stats", "/stats");
+        #[cfg(feature = "metrics")]
+        let posted = stats.clone();
+        let resp = post_with_meta(self, endpoint, Some(stats)).await?;
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.set_bot_stats(posted);
+        }
+        Ok(resp)
+    }
+
+    /// Same as [`post_server_stats`](Self::post_server_stats), with metadata.
+    pub async fn post_server_stats_with_meta(&self, stats: ServerStats) -> Result<ApiResponse<()>, BoticordError> {
+        let endpoint = endpoint!(self, "post_server_stats", "/server");
+        post_with_meta(self, endpoint, Some(stats)).await
+    }
+
+    /// Same as [`call`](Self::call), with metadata.
+    pub async fn call_with_meta<Req, Resp>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Req>,
+    ) -> Result<ApiResponse<Resp>, BoticordError>
+        where
+            Req: serde::Serialize,
+            Resp: serde::de::DeserializeOwned,
+    {
+        let endpoint = raw_endpoint(self, path)?;
+        let route = endpoint.route;
+        let fetched = fetch(self, endpoint, method, body).await?;
+        let value = if fetched.response.body.iter().all(u8::is_ascii_whitespace) {
+            decode(self, route, &HttpResponse { body: b"null".to_vec(), ..fetched.response.clone() })?
+        } else {
+            decode(self, route, &fetched.response)?
+        };
+        Ok(ApiResponse::new(value, fetched))
+    }
+}
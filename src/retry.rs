@@ -0,0 +1,68 @@
+//! Retries of failed requests.
+
+use std::time::Duration;
+
+/// When and how often [`BoticordClient`](crate::BoticordClient) retries requests.
+///
+/// Requests are retried on `429` responses. Idempotent requests are also
+/// retried when no response was received and on `5xx` responses, a `POST`
+/// may already have been applied then and is retried only with
+/// [`retry_non_idempotent`](Self::retry_non_idempotent). `Retry-After` is
+/// respected, otherwise the delay doubles after every attempt. Nothing is
+/// retried by default.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// How many times to retry a request.
+    pub max_retries: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Upper bound of any delay, including `Retry-After`.
+    pub max_delay: Duration,
+    /// Whether non-idempotent requests are retried on `5xx` responses and
+    /// transport errors too.
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::none()
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            retry_non_idempotent: false,
+        }
+    }
+
+    /// Retry up to `max_retries` times with default delays.
+    pub fn retries(max_retries: u32) -> Self {
+        RetryPolicy { max_retries, ..RetryPolicy::none() }
+    }
+
+    /// Sets whether non-idempotent requests are retried on `5xx` responses
+    /// and transport errors.
+    pub fn with_non_idempotent(mut self, retry: bool) -> Self {
+        self.retry_non_idempotent = retry;
+        self
+    }
+
+    /// Whether a request with the method may be retried after a `5xx`
+    /// response or a transport error.
+    pub fn retries_failures_of(&self, method: &http::Method) -> bool {
+        self.retry_non_idempotent || method.is_idempotent()
+    }
+
+    /// Delay before the retry number `attempt` (starting from `0`).
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let delay = retry_after.unwrap_or_else(|| {
+            self.base_delay.saturating_mul(2u32.saturating_pow(attempt))
+        });
+        delay.min(self.max_delay)
+    }
+}
@@ -0,0 +1,178 @@
+//! [`tower`] integration.
+//!
+//! Available with the `tower` feature. It works both ways:
+//!
+//! * [`BoticordClient`] is a [`Service`] of [`ApiCall`]s, so tower layers can
+//!   wrap whole API calls: token, retries, error mapping and decoding included.
+//! * [`TransportService`] turns any [`HttpTransport`] into a [`Service`] of
+//!   HTTP requests, and [`ServiceTransport`] turns a stack built on top of it
+//!   back into a transport for [`BoticordClient::new_with_service`].
+//!
+//! ```no_run
+//! use boticordrs::service::ApiCall;
+//! use boticordrs::BoticordClient;
+//! use tower::{Service, ServiceBuilder, ServiceExt};
+//!
+//! use std::time::Duration;
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let client = BoticordClient::new("your token", 2)?;
+//! let mut calls = ServiceBuilder::new()
+//!     .timeout(Duration::from_secs(5))
+//!     .concurrency_limit(4)
+//!     .service(client);
+//!
+//! let bot = calls.ready().await?.call(ApiCall::get("/bot/724663360934772797")).await?;
+//! println!("{}", bot.value["shortCode"]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ```no_run
+//! use boticordrs::service::TransportService;
+//! use boticordrs::BoticordClient;
+//! use tower::ServiceBuilder;
+//!
+//! use std::time::Duration;
+//!
+//! let stack = ServiceBuilder::new()
+//!     .timeout(Duration::from_secs(5))
+//!     .concurrency_limit(4)
+//!     .service(TransportService::new(reqwest::Client::new()));
+//!
+//! let client = BoticordClient::new_with_service(stack, "your token".to_string(), 2);
+//! ```
+//!
+//! Errors of the stack are passed through when they are [`BoticordError`]s
+//! and wrapped into [`BoticordError::Transport`] otherwise (e.g. timeouts).
+//!
+//! [`BoticordClient::new_with_service`]: crate::BoticordClient::new_with_service
+
+use http::Method;
+use tokio::sync::Mutex;
+use tower::{Service, ServiceExt};
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::response::ApiResponse;
+use crate::transport::{BoxFuture, HttpRequest, HttpResponse, HttpTransport};
+use crate::{BoticordClient, BoticordError};
+
+/// API call made by the [`Service`] implementation of [`BoticordClient`].
+///
+/// Same as the arguments of [`BoticordClient::call_value`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiCall {
+    /// Request method.
+    pub method: Method,
+    /// Path relative to the API version, e.g. `/bot/{id}/comments`.
+    pub path: String,
+    /// JSON body of the request.
+    pub body: Option<serde_json::Value>,
+}
+
+impl ApiCall {
+    /// Call with the method, path and body.
+    pub fn new(method: Method, path: impl Into<String>, body: Option<serde_json::Value>) -> Self {
+        ApiCall { method, path: path.into(), body }
+    }
+
+    /// GET call of the path.
+    pub fn get(path: impl Into<String>) -> Self {
+        ApiCall::new(Method::GET, path, None)
+    }
+
+    /// POST call of the path with the body.
+    pub fn post(path: impl Into<String>, body: serde_json::Value) -> Self {
+        ApiCall::new(Method::POST, path, Some(body))
+    }
+}
+
+impl Service<ApiCall> for BoticordClient {
+    type Response = ApiResponse<serde_json::Value>;
+    type Error = BoticordError;
+    type Future = BoxFuture<'static, Result<Self::Response, BoticordError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, call: ApiCall) -> Self::Future {
+        let client = self.clone();
+        Box::pin(async move { client.call_with_meta(call.method, &call.path, call.body).await })
+    }
+}
+
+/// [`Service`] that sends requests with a [`HttpTransport`].
+pub struct TransportService<T> {
+    transport: Arc<T>,
+}
+
+impl<T> Clone for TransportService<T> {
+    fn clone(&self) -> Self {
+        TransportService { transport: Arc::clone(&self.transport) }
+    }
+}
+
+impl<T: HttpTransport + 'static> TransportService<T> {
+    /// Wraps the transport.
+    pub fn new(transport: T) -> Self {
+        TransportService { transport: Arc::new(transport) }
+    }
+}
+
+impl<T: HttpTransport + 'static> Service<HttpRequest> for TransportService<T> {
+    type Response = HttpResponse;
+    type Error = BoticordError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoticordError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let transport = Arc::clone(&self.transport);
+        Box::pin(async move { transport.send(request).await })
+    }
+}
+
+/// [`HttpTransport`] that sends requests through a [`Service`].
+///
+/// Requests take turns to get the service ready and start the call, the
+/// responses are awaited concurrently. The service doesn't have to be
+/// `Clone` or `Sync`, so `tower::util::BoxService` works too.
+pub struct ServiceTransport<S> {
+    service: Mutex<S>,
+}
+
+impl<S> ServiceTransport<S> {
+    /// Wraps the service.
+    pub fn new(service: S) -> Self {
+        ServiceTransport { service: Mutex::new(service) }
+    }
+}
+
+impl<S> HttpTransport for ServiceTransport<S>
+    where
+        S: Service<HttpRequest, Response = HttpResponse> + Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        S::Future: Send,
+{
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoticordError>> {
+        Box::pin(async move {
+            let response = {
+                let mut service = self.service.lock().await;
+                service.ready().await.map_err(into_error)?.call(request)
+            };
+            response.await.map_err(into_error)
+        })
+    }
+}
+
+fn into_error<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> BoticordError {
+    match e.into().downcast::<BoticordError>() {
+        Ok(e) => *e,
+        Err(e) => BoticordError::Transport(e),
+    }
+}
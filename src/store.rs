@@ -0,0 +1,123 @@
+//! Stores for the state of [`BumpManager`], [`ReminderScheduler`] and
+//! [`CommentWatcher`].
+//!
+//! [`MemoryStore`] keeps nothing, [`JsonFileStore`] keeps the state in a JSON
+//! file. Both work with any of them, other storage implements their
+//! [`BumpStore`], [`ReminderStore`] or [`CommentStore`] trait.
+//!
+//! ```no_run
+//! use boticordrs::bump::BumpManager;
+//! use boticordrs::reminder::ReminderScheduler;
+//! use boticordrs::store::JsonFileStore;
+//! use boticordrs::BoticordClient;
+//!
+//! let client = BoticordClient::new("your token", 2).expect("failed client");
+//! let bumps = BumpManager::new(client, JsonFileStore::new("bumps.json")).expect("failed to load bumps");
+//! let reminders = ReminderScheduler::new(JsonFileStore::new("reminders.json")).expect("failed to load reminders");
+//! ```
+//!
+//! [`BumpManager`]: crate::bump::BumpManager
+//! [`ReminderScheduler`]: crate::reminder::ReminderScheduler
+//! [`CommentWatcher`]: crate::watcher::CommentWatcher
+//! [`BumpStore`]: crate::bump::BumpStore
+//! [`ReminderStore`]: crate::reminder::ReminderStore
+//! [`CommentStore`]: crate::watcher::CommentStore
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::BoticordError;
+
+/// Store that keeps nothing, the state is lost on restart.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MemoryStore;
+
+/// Store that keeps the state `T` in a JSON file.
+pub struct JsonFileStore<T> {
+    path: PathBuf,
+    state: PhantomData<fn() -> T>,
+}
+
+impl<T> JsonFileStore<T> {
+    /// Creates a store backed by the file at `path`.
+    /// The file is created on the first save.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonFileStore { path: path.into(), state: PhantomData }
+    }
+
+    /// Path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn load(&self) -> Result<T, BoticordError>
+        where
+            T: serde::de::DeserializeOwned + Default,
+    {
+        load_json(&self.path)
+    }
+
+    pub(crate) fn save<V: serde::Serialize + ?Sized>(&self, state: &V) -> Result<(), BoticordError> {
+        save_json(&self.path, state)
+    }
+}
+
+impl<T> Clone for JsonFileStore<T> {
+    fn clone(&self) -> Self {
+        JsonFileStore::new(self.path.clone())
+    }
+}
+
+impl<T> fmt::Debug for JsonFileStore<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonFileStore").field("path", &self.path).finish()
+    }
+}
+
+pub(crate) fn to_unix(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+pub(crate) fn from_unix(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// FNV-1a hash, stable across runs and platforms.
+pub(crate) fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325u64, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+pub(crate) fn load_json<T>(path: &Path) -> Result<T, BoticordError>
+    where
+        T: serde::de::DeserializeOwned + Default,
+{
+    match std::fs::read(path) {
+        Ok(data) => serde_json::from_slice(&data).map_err(|e| BoticordError::Store(e.into())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(BoticordError::Store(e)),
+    }
+}
+
+pub(crate) fn save_json<T>(path: &Path, value: &T) -> Result<(), BoticordError>
+    where
+        T: serde::Serialize + ?Sized,
+{
+    let data = serde_json::to_vec_pretty(value).map_err(|e| BoticordError::Store(e.into()))?;
+    // unique, so stores sharing a directory or a file name don't clash
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed),
+    ));
+    std::fs::write(&tmp, data).map_err(BoticordError::Store)?;
+    std::fs::rename(&tmp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        BoticordError::Store(e)
+    })
+}
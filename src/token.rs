@@ -0,0 +1,332 @@
+//! BotiCord API token.
+//!
+//! [`Token`] keeps the token out of logs: it prints as `[REDACTED]`, is
+//! wiped from memory when dropped, and the client never puts it into errors,
+//! traces or cassettes.
+//!
+//! ```no_run
+//! use boticordrs::token::Token;
+//! use boticordrs::BoticordClient;
+//!
+//! let token = Token::from_env("BOTICORD_TOKEN").expect("no token");
+//! let client = BoticordClient::new(token, 2).expect("failed client");
+//! ```
+//!
+//! The `Authorization` header is built from the token with the scheme of the
+//! API version (see [`AuthScheme`]), so a token can be passed with or without
+//! the `Bot ` prefix. A rejected token that looks like it was issued for
+//! another API version is reported as [`BoticordError::TokenMismatch`].
+//!
+//! Tokens that change while the bot runs come from a [`TokenProvider`],
+//! which the client asks for the token before every request:
+//!
+//! ```no_run
+//! use boticordrs::token::FileToken;
+//! use boticordrs::BoticordClient;
+//!
+//! let client = BoticordClient::new("", 2)
+//!     .expect("failed client")
+//!     .with_token_provider(FileToken::new("/run/secrets/boticord"));
+//! ```
+
+use zeroize::Zeroize;
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::BoticordError;
+
+/// Placeholder printed instead of the token.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Secret BotiCord token.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Token(String);
+
+impl Token {
+    /// Wraps the token.
+    pub fn new(token: impl Into<String>) -> Self {
+        Token(token.into())
+    }
+
+    /// Reads the token from an environment variable.
+    pub fn from_env(var: &str) -> Result<Self, BoticordError> {
+        let mut value = std::env::var(var).map_err(|e| {
+            BoticordError::Validation(format!("failed to read token from ${}: {}", var, e))
+        })?;
+        let token = Token::new(value.trim());
+        value.zeroize();
+        Ok(token)
+    }
+
+    /// Reads the token from a file, surrounding whitespace is ignored.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, BoticordError> {
+        let path = path.as_ref();
+        let mut contents = std::fs::read_to_string(path).map_err(|e| {
+            BoticordError::Validation(format!("failed to read token from {}: {}", path.display(), e))
+        })?;
+        let token = Token::new(contents.trim());
+        contents.zeroize();
+        Ok(token)
+    }
+
+    /// Returns the token itself.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Token without the scheme prefix, e.g. `abc` for `Bot abc`.
+    pub fn raw(&self) -> &str {
+        let token = self.0.trim();
+        match token.split_once(' ') {
+            Some((scheme, rest)) if AuthScheme::parse(scheme).is_some() => rest.trim_start(),
+            _ => token,
+        }
+    }
+
+    /// Scheme the token was given with, if any.
+    pub fn scheme(&self) -> Option<AuthScheme> {
+        self.0.trim().split_once(' ').and_then(|(scheme, _)| AuthScheme::parse(scheme))
+    }
+
+    /// Value of the `Authorization` header for the API version.
+    ///
+    /// `Profile` tokens keep their scheme on versions that support it,
+    /// any other token gets the version's default scheme. Fails for unknown
+    /// versions and for tokens prefixed with an unknown scheme.
+    pub fn authorization(&self, version: u64) -> Result<String, BoticordError> {
+        let token = self.0.trim();
+        if self.scheme().is_none() && token.contains(char::is_whitespace) {
+            let scheme = token.split_whitespace().next().unwrap_or_default();
+            return Err(BoticordError::Validation(format!("unknown auth scheme {:?}", scheme)));
+        }
+        let scheme = match (AuthScheme::for_version(version)?, self.scheme()) {
+            (AuthScheme::Bot, Some(AuthScheme::Profile)) => AuthScheme::Profile,
+            (scheme, _) => scheme,
+        };
+        Ok(match scheme.prefix() {
+            Some(prefix) => format!("{} {}", prefix, self.raw()),
+            None => self.raw().to_string(),
+        })
+    }
+
+    /// API versions the token looks like it was issued for.
+    ///
+    /// API v1 issued opaque tokens, API v2 issues JWTs. API v3 issues JWTs too
+    /// but is not one of [`VERSIONS`], so they count as v2 tokens. Empty for
+    /// an empty token.
+    pub fn guess_versions(&self) -> &'static [u64] {
+        let raw = self.raw();
+        if raw.is_empty() {
+            return &[];
+        }
+        let parts: Vec<&str> = raw.split('.').collect();
+        let jwt = parts.len() == 3
+            && raw.starts_with("eyJ")
+            && parts.iter().all(|p| {
+                !p.is_empty() && p.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+            });
+        if jwt {
+            &[2]
+        } else {
+            &[1]
+        }
+    }
+
+    /// Whether the token is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Replaces every occurrence of the token in `text` with [`REDACTED`].
+    ///
+    /// The token is redacted as given, without its scheme, and as any
+    /// `Authorization` header it is sent in.
+    pub(crate) fn redact(&self, text: &str) -> String {
+        let mut forms: Vec<String> = VERSIONS.iter().filter_map(|v| self.authorization(*v).ok()).collect();
+        forms.push(self.0.clone());
+        forms.push(self.raw().to_string());
+        // longest first, so a header is redacted whole and not around its token
+        forms.sort_by_key(|form| std::cmp::Reverse(form.len()));
+
+        let mut text = text.to_string();
+        for mut form in forms {
+            if !form.is_empty() {
+                text = text.replace(&form, REDACTED);
+            }
+            form.zeroize();
+        }
+        text
+    }
+}
+
+impl Drop for Token {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Token").field(&format_args!("{}", REDACTED)).finish()
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl From<String> for Token {
+    fn from(token: String) -> Self {
+        Token(token)
+    }
+}
+
+impl From<&str> for Token {
+    fn from(token: &str) -> Self {
+        Token::new(token)
+    }
+}
+
+/// BotiCord API versions the client supports.
+///
+/// API v3 wraps every response in a `result` envelope and reshapes most
+/// models, so it is left out until [`types`](crate::types) can decode it.
+pub const VERSIONS: [u64; 2] = [1, 2];
+
+/// How the token is written into the `Authorization` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthScheme {
+    /// Token as is, used by API v1.
+    Raw,
+    /// `Bot <token>`, used by API v2.
+    Bot,
+    /// `Profile <token>`, user tokens of API v2.
+    Profile,
+}
+
+impl AuthScheme {
+    /// Default scheme of the API version, one of [`VERSIONS`].
+    pub fn for_version(version: u64) -> Result<Self, BoticordError> {
+        match version {
+            1 => Ok(AuthScheme::Raw),
+            2 => Ok(AuthScheme::Bot),
+            _ => Err(BoticordError::Validation(format!("unsupported API version {}", version))),
+        }
+    }
+
+    /// Word written before the token.
+    pub fn prefix(self) -> Option<&'static str> {
+        match self {
+            AuthScheme::Raw => None,
+            AuthScheme::Bot => Some("Bot"),
+            AuthScheme::Profile => Some("Profile"),
+        }
+    }
+
+    fn parse(scheme: &str) -> Option<Self> {
+        if scheme.eq_ignore_ascii_case("bot") {
+            Some(AuthScheme::Bot)
+        } else if scheme.eq_ignore_ascii_case("profile") {
+            Some(AuthScheme::Profile)
+        } else {
+            None
+        }
+    }
+}
+
+/// Source of the token, consulted by the client before every request.
+pub trait TokenProvider: Send + Sync {
+    /// Returns the token to authorize the next request with.
+    fn token(&self) -> Result<Token, BoticordError>;
+}
+
+/// Always the same token.
+impl TokenProvider for Token {
+    fn token(&self) -> Result<Token, BoticordError> {
+        Ok(self.clone())
+    }
+}
+
+/// Token read from an environment variable on every request.
+#[derive(Clone, Debug)]
+pub struct EnvToken {
+    var: String,
+}
+
+impl EnvToken {
+    /// Reads the token from the variable `var`.
+    pub fn new(var: impl Into<String>) -> Self {
+        EnvToken { var: var.into() }
+    }
+}
+
+impl TokenProvider for EnvToken {
+    fn token(&self) -> Result<Token, BoticordError> {
+        Token::from_env(&self.var)
+    }
+}
+
+/// Token read from a file, reloaded whenever the file is modified.
+#[derive(Debug)]
+pub struct FileToken {
+    path: PathBuf,
+    cached: Mutex<Option<(SystemTime, Token)>>,
+}
+
+impl FileToken {
+    /// Reads the token from the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileToken { path: path.into(), cached: Mutex::new(None) }
+    }
+}
+
+impl TokenProvider for FileToken {
+    fn token(&self) -> Result<Token, BoticordError> {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .map_err(|e| {
+                BoticordError::Validation(format!("failed to read token from {}: {}", self.path.display(), e))
+            })?;
+
+        let mut cached = self.cached.lock().unwrap();
+        match &*cached {
+            Some((at, token)) if *at == modified => Ok(token.clone()),
+            _ => {
+                let token = Token::from_file(&self.path)?;
+                *cached = Some((modified, token.clone()));
+                Ok(token)
+            }
+        }
+    }
+}
+
+/// Token returned by a closure, see [`from_fn`].
+pub struct FnToken<F>(F);
+
+/// Calls `f` for the token before every request.
+///
+/// ```
+/// use boticordrs::token::{self, Token};
+///
+/// let provider = token::from_fn(|| Ok(Token::new("rotated token")));
+/// ```
+pub fn from_fn<F>(f: F) -> FnToken<F>
+    where
+        F: Fn() -> Result<Token, BoticordError> + Send + Sync,
+{
+    FnToken(f)
+}
+
+impl<F> TokenProvider for FnToken<F>
+    where
+        F: Fn() -> Result<Token, BoticordError> + Send + Sync,
+{
+    fn token(&self) -> Result<Token, BoticordError> {
+        (self.0)()
+    }
+}
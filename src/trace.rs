@@ -0,0 +1,121 @@
+//! Telemetry of API calls.
+//!
+//! With the `tracing` feature every call runs in an `INFO` span named
+//! `boticord.request`, carrying the client method, route, resource id and
+//! API version. The span records the final status, number of attempts and
+//! latency; every attempt emits a `DEBUG` event and failures a `WARN` one.
+//! Bodies are logged at `TRACE` only when enabled with
+//! [`BoticordClient::with_body_logging`]. The token is never recorded.
+//!
+//! Without the feature all of this compiles to nothing.
+
+use http::Method;
+use serde_json::Value;
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::token::Token;
+use crate::transport::HttpResponse;
+use crate::{BoticordClient, BoticordError, Endpoint};
+
+#[cfg(feature = "tracing")]
+pub(crate) type Span = tracing::Span;
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+#[cfg(feature = "tracing")]
+pub(crate) fn span(client: &BoticordClient, endpoint: &Endpoint, method: &Method) -> Span {
+    tracing::info_span!(
+        "boticord.request",
+        method = endpoint.name,
+        http.method = %method,
+        route = endpoint.route,
+        id = endpoint.id.as_deref(),
+        version = client.version,
+        http.status = tracing::field::Empty,
+        attempts = tracing::field::Empty,
+        latency_ms = tracing::field::Empty,
+    )
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn span(_client: &BoticordClient, _endpoint: &Endpoint, _method: &Method) -> Span {
+    Span
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn instrument<F: Future>(future: F, span: Span) -> impl Future<Output = F::Output> {
+    tracing::Instrument::instrument(future, span)
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn instrument<F: Future>(future: F, _span: Span) -> impl Future<Output = F::Output> {
+    future
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn request_body(client: &BoticordClient, token: &Token, body: Option<&Value>) {
+    if let (true, Some(body)) = (client.log_bodies, body) {
+        tracing::trace!(body = %token.redact(&body.to_string()), "request body");
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn request_body(_client: &BoticordClient, _token: &Token, _body: Option<&Value>) {}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn attempt(attempt: u32, result: &Result<HttpResponse, BoticordError>) {
+    match result {
+        Ok(resp) => tracing::debug!(attempt, status = resp.status.as_u16(), "response received"),
+        Err(e) => tracing::debug!(attempt, error = %e, "request failed"),
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn attempt(_attempt: u32, _result: &Result<HttpResponse, BoticordError>) {}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn finish(
+    client: &BoticordClient,
+    token: &Token,
+    result: &Result<HttpResponse, BoticordError>,
+    attempts: u32,
+    latency: Duration,
+) {
+    let span = tracing::Span::current();
+    span.record("attempts", attempts);
+    span.record("latency_ms", latency.as_millis() as u64);
+    match result {
+        Ok(resp) => {
+            span.record("http.status", resp.status.as_u16());
+            if client.log_bodies {
+                let body = token.redact(&String::from_utf8_lossy(&resp.body));
+                tracing::trace!(body = %body, "response body");
+            }
+        }
+        Err(e) => {
+            if let Some(status) = e.status() {
+                span.record("http.status", status.as_u16());
+            }
+            // the message of an API error includes its body
+            if client.log_bodies {
+                tracing::warn!(error = %e, kind = e.kind(), "request failed")
+            } else {
+                let status = e.status().map(|status| status.as_u16());
+                tracing::warn!(status, kind = e.kind(), "request failed")
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn finish(
+    _client: &BoticordClient,
+    _token: &Token,
+    _result: &Result<HttpResponse, BoticordError>,
+    _attempts: u32,
+    _latency: Duration,
+) {
+}
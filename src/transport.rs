@@ -0,0 +1,98 @@
+//! HTTP transport used by [`BoticordClient`].
+//!
+//! The client builds requests, authorizes them, retries and decodes
+//! responses itself, the transport only moves bytes. With the `reqwest`
+//! feature (enabled by default) [`reqwest::Client`] is used, any other
+//! HTTP stack can be plugged in by implementing [`HttpTransport`].
+//!
+//! ```
+//! use boticordrs::transport::{BoxFuture, HttpRequest, HttpResponse, HttpTransport};
+//! use boticordrs::BoticordError;
+//! use http::{HeaderMap, StatusCode};
+//!
+//! struct AlwaysEmpty;
+//!
+//! impl HttpTransport for AlwaysEmpty {
+//!     fn send(&self, _request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoticordError>> {
+//!         Box::pin(async {
+//!             Ok(HttpResponse { status: StatusCode::OK, headers: HeaderMap::new(), body: b"[]".to_vec() })
+//!         })
+//!     }
+//! }
+//! ```
+//!
+//! [`BoticordClient`]: crate::BoticordClient
+
+use http::{HeaderMap, Method, StatusCode};
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::BoticordError;
+
+/// Boxed future returned by [`HttpTransport::send`].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Request to send.
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    /// HTTP method.
+    pub method: Method,
+    /// Full URL.
+    pub url: String,
+    /// Headers, including `Authorization`.
+    pub headers: HeaderMap,
+    /// JSON body, if any.
+    pub body: Option<Vec<u8>>,
+}
+
+/// Received response.
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    /// Response status.
+    pub status: StatusCode,
+    /// Response headers.
+    pub headers: HeaderMap,
+    /// Response body.
+    pub body: Vec<u8>,
+}
+
+/// Sends HTTP requests for [`BoticordClient`](crate::BoticordClient).
+///
+/// Errors should be returned only when no response was received,
+/// [`BoticordError::Transport`] is meant for them. Such errors are retried.
+pub trait HttpTransport: Send + Sync {
+    /// Sends the request and reads the whole response.
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoticordError>>;
+}
+
+impl<T: HttpTransport + ?Sized> HttpTransport for Arc<T> {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoticordError>> {
+        (**self).send(request)
+    }
+}
+
+impl<T: HttpTransport + ?Sized> HttpTransport for Box<T> {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoticordError>> {
+        (**self).send(request)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpTransport for reqwest::Client {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoticordError>> {
+        Box::pin(async move {
+            let mut req = self.request(request.method, &request.url).headers(request.headers);
+            if let Some(body) = request.body {
+                req = req.body(body);
+            }
+
+            let resp = req.send().await.map_err(BoticordError::Reqwest)?;
+            let status = resp.status();
+            let headers = resp.headers().clone();
+            let body = resp.bytes().await.map_err(BoticordError::Reqwest)?.to_vec();
+            Ok(HttpResponse { status, headers, body })
+        })
+    }
+}
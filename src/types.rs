@@ -1,14 +1,216 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use url::Url;
 
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize,)]
+use std::fmt;
+
+use crate::BoticordError;
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub struct BotId(pub String);
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize,)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub struct ServerId(pub String);
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize,)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub struct UserId(pub String);
 
+/// (De)serializes `bool` as `1`/`0`, the way BotiCord expects flags.
+mod bool_as_int {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(*value as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        Ok(u64::deserialize(deserializer)? != 0)
+    }
+}
+
+/// Social media platform of a [`SocialLink`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Platform {
+    /// Any website.
+    Site,
+    /// Discord invite.
+    Discord,
+    /// GitHub profile or repository.
+    Github,
+    /// YouTube channel.
+    Youtube,
+    /// Twitch channel.
+    Twitch,
+    /// Steam profile.
+    Steam,
+    /// VK page or group.
+    Vk,
+}
+
+impl Platform {
+    /// Turns a handle, invite code or URL without scheme into a full URL.
+    ///
+    /// Falls back to [`SocialLink::Raw`] if no URL can be built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boticordrs::types::Platform;
+    ///
+    /// let link = Platform::Discord.normalize("hkHjW8a");
+    /// assert_eq!(link.as_str(), "https://discord.gg/hkHjW8a");
+    ///
+    /// let link = Platform::Github.normalize("@grey-cat-1908");
+    /// assert_eq!(link.as_str(), "https://github.com/grey-cat-1908");
+    /// ```
+    pub fn normalize(self, value: &str) -> SocialLink {
+        let value = value.trim();
+        if let Ok(url) = Url::parse(value) {
+            if url.scheme() == "http" || url.scheme() == "https" {
+                return SocialLink::Url(url);
+            }
+        }
+
+        let handle = value.trim_start_matches('@');
+        let candidate = if handle.is_empty() || handle.contains(char::is_whitespace) {
+            None
+        } else if self.is_host(handle) {
+            Some(format!("https://{}", handle))
+        } else {
+            match self {
+                Platform::Site => None,
+                Platform::Discord => Some(format!("https://discord.gg/{}", handle)),
+                Platform::Github => Some(format!("https://github.com/{}", handle)),
+                Platform::Youtube if handle.starts_with("UC") && handle.len() == 24 => {
+                    Some(format!("https://www.youtube.com/channel/{}", handle))
+                }
+                Platform::Youtube => Some(format!("https://www.youtube.com/@{}", handle)),
+                Platform::Twitch => Some(format!("https://www.twitch.tv/{}", handle)),
+                Platform::Steam if handle.len() == 17 && handle.bytes().all(|b| b.is_ascii_digit()) => {
+                    Some(format!("https://steamcommunity.com/profiles/{}", handle))
+                }
+                Platform::Steam => Some(format!("https://steamcommunity.com/id/{}", handle)),
+                Platform::Vk => Some(format!("https://vk.com/{}", handle)),
+            }
+        };
+
+        match candidate.and_then(|c| Url::parse(&c).ok()) {
+            Some(url) => SocialLink::Url(url),
+            None => SocialLink::Raw(value.to_string()),
+        }
+    }
+
+    /// Domains of the platform, any domain is a website.
+    fn domains(self) -> &'static [&'static str] {
+        match self {
+            Platform::Site => &[],
+            Platform::Discord => &["discord.gg", "discord.com", "discordapp.com"],
+            Platform::Github => &["github.com"],
+            Platform::Youtube => &["youtube.com", "youtu.be"],
+            Platform::Twitch => &["twitch.tv"],
+            Platform::Steam => &["steamcommunity.com"],
+            Platform::Vk => &["vk.com", "vk.ru"],
+        }
+    }
+
+    /// Whether the value is a URL without scheme rather than a handle.
+    ///
+    /// Handles may contain dots, e.g. `some.channel` on YouTube, so a dotted
+    /// value is only a host if it is a website, has a path or is on a domain
+    /// of the platform.
+    fn is_host(self, value: &str) -> bool {
+        let (host, has_path) = value.split_once('/').map_or((value, false), |(host, _)| (host, true));
+        let dotted = host.contains('.') && !host.starts_with('.') && !host.ends_with('.');
+        let host = host.to_ascii_lowercase();
+        let known = self.domains().iter().any(|domain| {
+            host == *domain || host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.'))
+        });
+        dotted && (self == Platform::Site || has_path || known)
+    }
+}
+
+/// This model represents a link to social media.
+///
+/// BotiCord stores whatever users typed in, so links that can't be
+/// turned into a URL are kept as is.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SocialLink {
+    /// Parsed and normalized URL.
+    Url(Url),
+    /// Original value that is not a valid URL.
+    Raw(String),
+}
+
+impl SocialLink {
+    /// Returns the URL, if the link was parsed.
+    pub fn as_url(&self) -> Option<&Url> {
+        match self {
+            SocialLink::Url(url) => Some(url),
+            SocialLink::Raw(_) => None,
+        }
+    }
+
+    /// Returns the link as a string.
+    pub fn as_str(&self) -> &str {
+        match self {
+            SocialLink::Url(url) => url.as_str(),
+            SocialLink::Raw(raw) => raw,
+        }
+    }
+}
+
+impl fmt::Display for SocialLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for SocialLink {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SocialLink {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Platform::Site.normalize(&String::deserialize(deserializer)?))
+    }
+}
+
+/// Deserializers for `Option<SocialLink>` fields of a specific platform.
+/// Empty strings are treated as missing links.
+mod social {
+    use super::*;
+
+    fn link<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        platform: Platform,
+    ) -> Result<Option<SocialLink>, D::Error> {
+        Ok(Option::<String>::deserialize(deserializer)?
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| platform.normalize(&v)))
+    }
+
+    macro_rules! platform_fn {
+        ($($name:ident => $platform:ident),*) => {
+            $(pub fn $name<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Option<SocialLink>, D::Error> {
+                link(deserializer, Platform::$platform)
+            })*
+        };
+    }
+
+    platform_fn!(
+        site => Site,
+        discord => Discord,
+        github => Github,
+        youtube => Youtube,
+        twitch => Twitch,
+        steam => Steam,
+        vk => Vk
+    );
+}
+
 /// This model represents information about the server that attached to the bot.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct BotServer {
     /// Id of server.
     pub id: ServerId,
@@ -27,63 +229,305 @@ pub struct BotStats {
     pub users: u64
 }
 
+/// This model represents server's members count.
+///
+/// BotiCord sends it as a `[total, online]` array.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MemberCounts {
+    /// Total members count.
+    pub total: u64,
+    /// Online members count.
+    pub online: u64,
+}
+
+impl MemberCounts {
+    /// Returns counts in the `[total, online]` form.
+    pub fn to_vec(self) -> Vec<u64> {
+        vec![self.total, self.online]
+    }
+}
+
+impl From<MemberCounts> for Vec<u64> {
+    fn from(counts: MemberCounts) -> Self {
+        counts.to_vec()
+    }
+}
+
+impl Serialize for MemberCounts {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        [self.total, self.online].serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MemberCounts {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        members::from_counts(&Vec::<u64>::deserialize(deserializer)?)
+    }
+}
+
+/// Deserializer for `Option<MemberCounts>` fields.
+/// Empty arrays are treated as missing counts.
+mod members {
+    use super::*;
+
+    pub(super) fn from_counts<E: serde::de::Error>(counts: &[u64]) -> Result<MemberCounts, E> {
+        match *counts {
+            [total, online] => Ok(MemberCounts { total, online }),
+            _ => Err(E::invalid_length(counts.len(), &"a [total, online] array")),
+        }
+    }
+
+    pub fn optional<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<MemberCounts>, D::Error> {
+        match Option::<Vec<u64>>::deserialize(deserializer)? {
+            Some(counts) if !counts.is_empty() => from_counts(&counts).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
 /// This model represents Server stats.
+///
+/// Use [`ServerStats::builder`] to construct it.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(try_from = "ServerStatsWire", into = "ServerStatsWire")]
 pub struct ServerStats {
     /// Server Id
-    #[serde(rename = "serverID")]
-    pub server_id: String,
-    /// Is this bump request? (`1` - yes, `0` - no)
-    pub up: u64,
-    /// Is bot in server members list? (`1` - yes, `0` - no)
-    pub status: u64,
+    pub server_id: ServerId,
+    /// Is this bump request?
+    pub up: bool,
+    /// Is bot in server members list?
+    pub status: bool,
     /// Server Name
-    #[serde(rename = "serverName")]
     pub server_name: Option<String>,
     /// Server Icon
-    #[serde(rename = "serverAvatar")]
     pub server_avatar: Option<String>,
     /// Server Members count (total, cached by the bot)
-    #[serde(rename = "serverMembersAllCount")]
-    pub server_members_all_count: Option<u64>,
+    total_members: Option<u64>,
     /// Online Server Members count (only currently online members count)
-    #[serde(rename = "serverMembersOnlineCount")]
-    pub server_members_online_count: Option<u64>,
+    online_members: Option<u64>,
     /// Server's Owner Id.
+    pub server_owner_id: Option<UserId>,
+}
+
+/// [`ServerStats`] the way BotiCord expects them.
+#[derive(Clone, Deserialize, Serialize)]
+struct ServerStatsWire {
+    #[serde(rename = "serverID")]
+    server_id: ServerId,
+    #[serde(with = "bool_as_int")]
+    up: bool,
+    #[serde(with = "bool_as_int")]
+    status: bool,
+    #[serde(rename = "serverName")]
+    server_name: Option<String>,
+    #[serde(rename = "serverAvatar")]
+    server_avatar: Option<String>,
+    #[serde(rename = "serverMembersAllCount")]
+    server_members_all_count: Option<u64>,
+    #[serde(rename = "serverMembersOnlineCount")]
+    server_members_online_count: Option<u64>,
     #[serde(rename = "serverOwnerID")]
-    pub server_owner_id: Option<String>,
+    server_owner_id: Option<UserId>,
+}
+
+impl From<ServerStats> for ServerStatsWire {
+    fn from(stats: ServerStats) -> Self {
+        ServerStatsWire {
+            server_id: stats.server_id,
+            up: stats.up,
+            status: stats.status,
+            server_name: stats.server_name,
+            server_avatar: stats.server_avatar,
+            server_members_all_count: stats.total_members,
+            server_members_online_count: stats.online_members,
+            server_owner_id: stats.server_owner_id,
+        }
+    }
+}
+
+impl TryFrom<ServerStatsWire> for ServerStats {
+    type Error = BoticordError;
+
+    fn try_from(wire: ServerStatsWire) -> Result<Self, Self::Error> {
+        ServerStatsBuilder {
+            stats: ServerStats {
+                server_id: wire.server_id,
+                up: wire.up,
+                status: wire.status,
+                server_name: wire.server_name,
+                server_avatar: wire.server_avatar,
+                total_members: wire.server_members_all_count,
+                online_members: wire.server_members_online_count,
+                server_owner_id: wire.server_owner_id,
+            },
+        }
+        .build()
+    }
+}
+
+impl ServerStats {
+    /// Starts building stats for the given server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boticordrs::types::{ServerId, ServerStats};
+    ///
+    /// let stats = ServerStats::builder(ServerId("724663360934772797".to_string()))
+    ///     .bump(true)
+    ///     .members(120, 34)
+    ///     .build()
+    ///     .expect("invalid stats");
+    /// ```
+    pub fn builder(server_id: ServerId) -> ServerStatsBuilder {
+        ServerStatsBuilder {
+            stats: ServerStats {
+                server_id,
+                up: false,
+                status: true,
+                server_name: None,
+                server_avatar: None,
+                total_members: None,
+                online_members: None,
+                server_owner_id: None,
+            },
+        }
+    }
+
+    /// Total members count.
+    pub fn total_members(&self) -> Option<u64> {
+        self.total_members
+    }
+
+    /// Online members count.
+    pub fn online_members(&self) -> Option<u64> {
+        self.online_members
+    }
+
+    /// Members count, `None` unless both total and online ones are set.
+    pub fn members(&self) -> Option<MemberCounts> {
+        Some(MemberCounts { total: self.total_members?, online: self.online_members? })
+    }
+}
+
+/// Builder for [`ServerStats`].
+#[derive(Clone, Debug)]
+pub struct ServerStatsBuilder {
+    stats: ServerStats,
+}
+
+impl ServerStatsBuilder {
+    /// Marks the request as a bump request. Defaults to `false`.
+    pub fn bump(mut self, bump: bool) -> Self {
+        self.stats.up = bump;
+        self
+    }
+
+    /// Sets whether the bot is in the server members list. Defaults to `true`.
+    pub fn bot_in_server(mut self, status: bool) -> Self {
+        self.stats.status = status;
+        self
+    }
+
+    /// Sets the server name.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.stats.server_name = Some(name.into());
+        self
+    }
+
+    /// Sets the server icon.
+    pub fn avatar(mut self, avatar: impl Into<String>) -> Self {
+        self.stats.server_avatar = Some(avatar.into());
+        self
+    }
+
+    /// Sets the total members count.
+    pub fn total_members(mut self, total: u64) -> Self {
+        self.stats.total_members = Some(total);
+        self
+    }
+
+    /// Sets the online members count.
+    pub fn online_members(mut self, online: u64) -> Self {
+        self.stats.online_members = Some(online);
+        self
+    }
+
+    /// Sets both total and online members count.
+    pub fn members(self, total: u64, online: u64) -> Self {
+        self.total_members(total).online_members(online)
+    }
+
+    /// Sets members count.
+    pub fn member_counts(self, members: MemberCounts) -> Self {
+        self.members(members.total, members.online)
+    }
+
+    /// Sets the server's owner.
+    pub fn owner(mut self, owner: UserId) -> Self {
+        self.stats.server_owner_id = Some(owner);
+        self
+    }
+
+    /// Validates and returns the stats.
+    ///
+    /// Fails with [`BoticordError::Validation`] if the server id is empty
+    /// or the online members count is greater than the total one.
+    pub fn build(self) -> Result<ServerStats, BoticordError> {
+        if self.stats.server_id.0.is_empty() {
+            return Err(BoticordError::Validation("server id is empty".to_string()));
+        }
+        if let Some(members) = self.stats.members() {
+            if members.online > members.total {
+                return Err(BoticordError::Validation(format!(
+                    "online members count ({}) is greater than total ({})",
+                    members.online, members.total
+                )));
+            }
+        }
+        Ok(self.stats)
+    }
 }
 
 /// This model represents Bot's social medias.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct BotLinks {
     /// Bot's support server.
-    pub discord: Option<String>,
+    #[serde(default, deserialize_with = "social::discord")]
+    pub discord: Option<SocialLink>,
     /// Bot's github repo.
-    pub github: Option<String>,
+    #[serde(default, deserialize_with = "social::github")]
+    pub github: Option<SocialLink>,
     /// Bot's website.
-    pub site: Option<String>
+    #[serde(default, deserialize_with = "social::site")]
+    pub site: Option<SocialLink>
 }
 
 /// This model represents Server's social medias.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ServerLinks {
     /// Server's invite.
-    pub invite: Option<String>,
+    #[serde(default, deserialize_with = "social::discord")]
+    pub invite: Option<SocialLink>,
     /// Server's website.
-    pub site: Option<String>,
+    #[serde(default, deserialize_with = "social::site")]
+    pub site: Option<SocialLink>,
     /// Server's youtube channel.
-    pub youtube: Option<String>,
+    #[serde(default, deserialize_with = "social::youtube")]
+    pub youtube: Option<SocialLink>,
     /// Server's twitch channel.
-    pub twitch: Option<String>,
+    #[serde(default, deserialize_with = "social::twitch")]
+    pub twitch: Option<SocialLink>,
     /// Server's steam profile.
-    pub steam: Option<String>,
+    #[serde(default, deserialize_with = "social::steam")]
+    pub steam: Option<SocialLink>,
     /// Server's VK group.
-    pub vk: Option<String>
+    #[serde(default, deserialize_with = "social::vk")]
+    pub vk: Option<SocialLink>
 }
 
 /// This model represents Information about the bot.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BotInformation {
     /// Bumps count.
@@ -117,7 +561,7 @@ pub struct BotInformation {
 }
 
 /// This model represents Information about the server.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerInformation {
     /// Server's name.
@@ -125,7 +569,8 @@ pub struct ServerInformation {
     /// Server's avatar.
     pub avatar: Option<String>,
     /// Server's members count
-    pub members: Option<Vec<u64>>,
+    #[serde(default, deserialize_with = "members::optional")]
+    pub members: Option<MemberCounts>,
     /// Server's owner.
     pub owner: Option<UserId>,
     /// Bumps count.
@@ -144,9 +589,17 @@ pub struct ServerInformation {
     pub badge: Option<String>,
 }
 
+impl ServerInformation {
+    /// Server's members count in the old `[total, online]` form.
+    #[deprecated(note = "use `members` instead")]
+    pub fn members_vec(&self) -> Option<Vec<u64>> {
+        self.members.map(MemberCounts::to_vec)
+    }
+}
+
 
 /// Model that represents a bot.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Bot {
     /// Bot's Id.
@@ -162,7 +615,7 @@ pub struct Bot {
 }
 
 /// Model that represents a server.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Server {
     /// Server's Id.
@@ -178,7 +631,7 @@ pub struct Server {
 }
 
 /// This model represents single comment.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SingleComment {
     /// Comment author Id.
     #[serde(rename = "userID")]
@@ -192,14 +645,14 @@ pub struct SingleComment {
     pub is_updated: bool,
     /// Comment creation timestamp.
     #[serde(rename = "created_at")]
-    created_at: Option<u64>,
+    pub created_at: Option<u64>,
     /// Las edit timestamp.
     #[serde(rename = "updated_at")]
-    updated_at: Option<u64>,
+    pub updated_at: Option<u64>,
 }
 
 /// This model represents single bot.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SingleUserBot {
     /// Bot's id.
     pub id: BotId,
@@ -209,7 +662,7 @@ pub struct SingleUserBot {
 }
 
 /// This model represents information about user.
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UserInformation {
     /// Id of user
     pub id: UserId,
@@ -221,21 +674,27 @@ pub struct UserInformation {
     #[serde(rename = "shortCode")]
     pub short_code: Option<String>,
     /// User's Website
-    pub site: Option<String>,
+    #[serde(default, deserialize_with = "social::site")]
+    pub site: Option<SocialLink>,
     /// User's VK page
-    pub vk: Option<String>,
+    #[serde(default, deserialize_with = "social::vk")]
+    pub vk: Option<SocialLink>,
     /// User's steam profile
-    pub steam: Option<String>,
+    #[serde(default, deserialize_with = "social::steam")]
+    pub steam: Option<SocialLink>,
     /// User's youtube channel
-    pub youtube: Option<String>,
+    #[serde(default, deserialize_with = "social::youtube")]
+    pub youtube: Option<SocialLink>,
     /// User's twitch account
-    pub twitch: Option<String>,
+    #[serde(default, deserialize_with = "social::twitch")]
+    pub twitch: Option<SocialLink>,
     /// User's githup profile
-    pub git: Option<String>
+    #[serde(default, deserialize_with = "social::github")]
+    pub git: Option<SocialLink>
 }
 
 /// This model represents user's comments
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UserComments {
     /// Comments on bots pages
     pub bots: Option<Vec<SingleComment>>,
@@ -255,8 +714,19 @@ pub enum LinkDomain {
     DiscordCamp = 3
 }
 
+impl LinkDomain {
+    /// Returns the domain name, e.g. ``bcord.cc``.
+    pub fn host(&self) -> &'static str {
+        match self {
+            LinkDomain::BCordCC => "bcord.cc",
+            LinkDomain::MyServersMe => "myservers.me",
+            LinkDomain::DiscordCamp => "discord.camp",
+        }
+    }
+}
+
 /// This model represents shorted link
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ShortedLink {
     /// Id of shorted link
     pub id: u64,
@@ -0,0 +1,265 @@
+//! Comment change feed.
+//!
+//! BotiCord has no webhooks for comments, so [`CommentWatcher`] polls the
+//! comments of bots and servers, compares every result with the previous
+//! one, and turns the difference into [`CommentEvent`]s. An author has one
+//! comment per page, so comments are matched by author.
+//!
+//! The last seen comments are kept in a [`CommentStore`]. The first poll of
+//! a target only takes a snapshot, and after a restart only what changed
+//! while the bot was offline is reported.
+//!
+//! ```no_run
+//! use boticordrs::types::BotId;
+//! use boticordrs::store::JsonFileStore;
+//! use boticordrs::watcher::{CommentEvent, CommentWatcher};
+//! use boticordrs::BoticordClient;
+//! use futures_util::StreamExt;
+//!
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = BoticordClient::new("your token", 2).expect("failed client");
+//!     let store = JsonFileStore::new("comments.json");
+//!     let mut events = CommentWatcher::new(client, store)
+//!         .expect("failed to load snapshots")
+//!         .watch_bot(BotId("724663360934772797".to_string()))
+//!         .with_interval(Duration::from_secs(5 * 60))
+//!         .into_stream();
+//!
+//!     while let Some(event) = events.next().await {
+//!         match event {
+//!             Ok(CommentEvent::Added { comment, .. }) => println!("new review: {}", comment.text),
+//!             Ok(event) => println!("{:?}", event),
+//!             Err(e) => eprintln!("{}", e),
+//!         }
+//!     }
+//! }
+//! ```
+
+use futures_core::Stream;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::pin::Pin;
+use std::time::Duration;
+
+use crate::store::{JsonFileStore, MemoryStore};
+use crate::types::{BotId, ServerId, SingleComment};
+use crate::{BoticordClient, BoticordError};
+
+/// Poll interval of [`CommentWatcher::new`].
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Stream of comment events, never ends.
+pub type CommentEventStream = Pin<Box<dyn Stream<Item = Result<CommentEvent, BoticordError>> + Send>>;
+
+/// Page whose comments are watched.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
+#[serde(tag = "type", content = "id", rename_all = "lowercase")]
+pub enum CommentTarget {
+    /// Bot page.
+    Bot(BotId),
+    /// Server page.
+    Server(ServerId),
+}
+
+/// Change of the comments of a target.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommentEvent {
+    /// Author commented for the first time.
+    Added { target: CommentTarget, comment: SingleComment },
+    /// Author changed their comment.
+    Edited { target: CommentTarget, before: SingleComment, after: SingleComment },
+    /// Comment of the author is gone.
+    Removed { target: CommentTarget, comment: SingleComment },
+}
+
+impl CommentEvent {
+    /// Target of the changed comment.
+    pub fn target(&self) -> &CommentTarget {
+        match self {
+            CommentEvent::Added { target, .. }
+            | CommentEvent::Edited { target, .. }
+            | CommentEvent::Removed { target, .. } => target,
+        }
+    }
+}
+
+/// Last seen comments of a target.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CommentSnapshot {
+    /// Watched page.
+    pub target: CommentTarget,
+    /// Its comments.
+    pub comments: Vec<SingleComment>,
+}
+
+/// Storage for [`CommentWatcher`] snapshots.
+pub trait CommentStore: Send + Sync {
+    /// Loads all saved snapshots.
+    fn load(&self) -> Result<Vec<CommentSnapshot>, BoticordError>;
+
+    /// Replaces saved snapshots with the given ones.
+    fn save(&self, snapshots: &[CommentSnapshot]) -> Result<(), BoticordError>;
+}
+
+impl CommentStore for MemoryStore {
+    fn load(&self) -> Result<Vec<CommentSnapshot>, BoticordError> {
+        Ok(Vec::new())
+    }
+
+    fn save(&self, _snapshots: &[CommentSnapshot]) -> Result<(), BoticordError> {
+        Ok(())
+    }
+}
+
+impl CommentStore for JsonFileStore<Vec<CommentSnapshot>> {
+    fn load(&self) -> Result<Vec<CommentSnapshot>, BoticordError> {
+        JsonFileStore::load(self)
+    }
+
+    fn save(&self, snapshots: &[CommentSnapshot]) -> Result<(), BoticordError> {
+        JsonFileStore::save(self, snapshots)
+    }
+}
+
+/// Polls comments and reports how they change.
+pub struct CommentWatcher<S = MemoryStore> {
+    client: BoticordClient,
+    store: S,
+    targets: Vec<CommentTarget>,
+    interval: Duration,
+    snapshots: BTreeMap<CommentTarget, Vec<SingleComment>>,
+}
+
+impl<S: CommentStore + 'static> CommentWatcher<S> {
+    /// Constructs a new watcher, loading snapshots from `store`.
+    pub fn new(client: BoticordClient, store: S) -> Result<Self, BoticordError> {
+        let snapshots = store.load()?.into_iter().map(|s| (s.target, s.comments)).collect();
+        Ok(CommentWatcher { client, store, targets: Vec::new(), interval: DEFAULT_INTERVAL, snapshots })
+    }
+
+    /// Watches comments of the bot.
+    pub fn watch_bot(self, bot: BotId) -> Self {
+        self.watch(CommentTarget::Bot(bot))
+    }
+
+    /// Watches comments of the server.
+    pub fn watch_server(self, server: ServerId) -> Self {
+        self.watch(CommentTarget::Server(server))
+    }
+
+    /// Watches comments of the target.
+    pub fn watch(mut self, target: CommentTarget) -> Self {
+        if !self.targets.contains(&target) {
+            self.targets.push(target);
+        }
+        self
+    }
+
+    /// Sets the delay between polls, [`DEFAULT_INTERVAL`] by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Last seen comments of the target.
+    pub fn snapshot(&self, target: &CommentTarget) -> Option<&[SingleComment]> {
+        self.snapshots.get(target).map(Vec::as_slice)
+    }
+
+    /// Polls every target once.
+    ///
+    /// A target that fails, including when its snapshot can't be saved, keeps
+    /// its snapshot and is reported in the result, the others are polled anyway.
+    pub async fn poll(&mut self) -> Vec<Result<Vec<CommentEvent>, BoticordError>> {
+        let mut results = Vec::with_capacity(self.targets.len());
+        for target in self.targets.clone() {
+            results.push(self.poll_target(target).await);
+        }
+        results
+    }
+
+    /// Polls the targets every interval, starting right away.
+    ///
+    /// Failed polls are reported as errors and the stream goes on.
+    pub fn into_stream(self) -> CommentEventStream {
+        let state = (self, VecDeque::new(), true);
+        Box::pin(futures_util::stream::unfold(state, |(mut watcher, mut queue, mut first)| async move {
+            loop {
+                if let Some(item) = queue.pop_front() {
+                    return Some((item, (watcher, queue, first)));
+                }
+                if !first {
+                    tokio::time::sleep(watcher.interval).await;
+                }
+                first = false;
+                for result in watcher.poll().await {
+                    match result {
+                        Ok(events) => queue.extend(events.into_iter().map(Ok)),
+                        Err(e) => queue.push_back(Err(e)),
+                    }
+                }
+            }
+        }))
+    }
+
+    async fn poll_target(&mut self, target: CommentTarget) -> Result<Vec<CommentEvent>, BoticordError> {
+        let comments = match &target {
+            CommentTarget::Bot(bot) => self.client.get_bot_comments(bot.0.clone()).await?,
+            CommentTarget::Server(server) => self.client.get_server_comments(server.0.clone()).await?,
+        };
+        let events = match self.snapshots.get(&target) {
+            Some(before) if *before == comments => return Ok(Vec::new()),
+            Some(before) => diff(&target, before, &comments),
+            // the first poll only takes a snapshot
+            None => Vec::new(),
+        };
+
+        // the snapshot changes only once saved, so failed saves report the events again
+        let snapshots: Vec<_> = self.snapshots.iter()
+            .filter(|(saved, _)| **saved != target)
+            .map(|(target, comments)| CommentSnapshot { target: target.clone(), comments: comments.clone() })
+            .chain(std::iter::once(CommentSnapshot { target: target.clone(), comments: comments.clone() }))
+            .collect();
+        self.store.save(&snapshots)?;
+        self.snapshots.insert(target, comments);
+        Ok(events)
+    }
+}
+
+/// Events that turn `before` into `after`, matching comments by author.
+///
+/// A comment is edited when its text, vote, or edit flag and time changed.
+pub fn diff(target: &CommentTarget, before: &[SingleComment], after: &[SingleComment]) -> Vec<CommentEvent> {
+    let old: HashMap<&str, &SingleComment> = before.iter().map(|c| (c.user_id.as_str(), c)).collect();
+    let new: HashMap<&str, &SingleComment> = after.iter().map(|c| (c.user_id.as_str(), c)).collect();
+    let mut events = Vec::new();
+
+    for comment in after {
+        match old.get(comment.user_id.as_str()) {
+            None => events.push(CommentEvent::Added { target: target.clone(), comment: comment.clone() }),
+            Some(old) if is_edited(old, comment) => events.push(CommentEvent::Edited {
+                target: target.clone(),
+                before: (*old).clone(),
+                after: comment.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for comment in before {
+        if !new.contains_key(comment.user_id.as_str()) {
+            events.push(CommentEvent::Removed { target: target.clone(), comment: comment.clone() });
+        }
+    }
+    events
+}
+
+fn is_edited(before: &SingleComment, after: &SingleComment) -> bool {
+    after.updated_at != before.updated_at
+        || after.is_updated != before.is_updated
+        || after.text != before.text
+        || after.vote != before.vote
+}
@@ -0,0 +1,162 @@
+//! Server bumps with cooldown tracking.
+
+use boticordrs::bump::{BumpManager, DEFAULT_BUMP_COOLDOWN};
+use boticordrs::store::{JsonFileStore, MemoryStore};
+use boticordrs::transport::HttpResponse;
+use boticordrs::types::{ServerId, ServerStats};
+use boticordrs::{BoticordClient, BoticordError};
+use http::header::RETRY_AFTER;
+use http::{HeaderValue, StatusCode};
+
+use std::time::{Duration, SystemTime};
+
+mod common;
+
+use common::{response, Scripted};
+
+fn server() -> ServerId {
+    ServerId("1".to_string())
+}
+
+fn stats() -> ServerStats {
+    ServerStats::builder(server()).build().unwrap()
+}
+
+fn too_early(seconds: &'static str) -> HttpResponse {
+    let mut resp = response(429, "{\"error\":\"too early\"}");
+    resp.headers.insert(RETRY_AFTER, HeaderValue::from_static(seconds));
+    resp
+}
+
+/// Records keep whole seconds.
+fn secs(time: Option<SystemTime>) -> Option<u64> {
+    time.map(|time| time.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs())
+}
+
+fn manager(transport: &Scripted) -> BumpManager {
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+    BumpManager::new(client, MemoryStore).unwrap().with_cooldown(Duration::from_secs(60))
+}
+
+#[tokio::test]
+async fn refuses_bumps_during_cooldown() {
+    let transport = Scripted::replies(&[(200, "{}")]);
+    let manager = manager(&transport);
+
+    let next = manager.bump(stats()).await.expect("failed bump");
+    assert!(next > SystemTime::now() + Duration::from_secs(55));
+    assert_eq!(secs(manager.next_bump_at(&server()).await), secs(Some(next)));
+    assert!(manager.last_bump_at(&server()).await.is_some());
+
+    // nothing is sent until the cooldown ends
+    assert!(matches!(manager.bump(stats()).await, Err(BoticordError::BumpCooldown(at)) if secs(Some(at)) == secs(Some(next))));
+    assert_eq!(transport.calls(), 1);
+    let body: serde_json::Value = serde_json::from_slice(transport.requests()[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(body["up"], 1);
+}
+
+#[tokio::test]
+async fn honors_early_bump_rejections() {
+    let transport = Scripted::new().respond(too_early("120")).reply(429, "{\"error\":\"slow down\"}");
+    let manager = manager(&transport);
+
+    let next = match manager.bump(stats()).await {
+        Err(BoticordError::BumpCooldown(next)) => next,
+        other => panic!("unexpected result: {:?}", other),
+    };
+    assert!(next > SystemTime::now() + Duration::from_secs(115));
+    assert_eq!(secs(manager.next_bump_at(&server()).await), secs(Some(next)));
+    assert_eq!(manager.last_bump_at(&server()).await, None);
+
+    // without Retry-After the cooldown starts now, no earlier bump is known
+    manager.forget(&server()).await.unwrap();
+    let next = match manager.bump(stats()).await {
+        Err(BoticordError::BumpCooldown(next)) => next,
+        other => panic!("unexpected result: {:?}", other),
+    };
+    assert!(next > SystemTime::now() + Duration::from_secs(55));
+    assert_eq!(transport.calls(), 2);
+}
+
+#[tokio::test]
+async fn counts_the_cooldown_from_the_last_bump() {
+    let transport = Scripted::new().reply(200, "{}").reply(429, "{\"error\":\"slow down\"}");
+    let path = std::env::temp_dir().join(format!("boticordrs-bumps-early-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+    let manager = BumpManager::new(client.clone(), JsonFileStore::new(&path)).unwrap().with_cooldown(Duration::ZERO);
+    manager.bump(stats()).await.expect("failed bump");
+    let bumped = manager.last_bump_at(&server()).await.expect("no last bump");
+    drop(manager);
+
+    // restarted with the real cooldown, which BotiCord enforces from the last bump
+    let manager = BumpManager::new(client, JsonFileStore::new(&path)).unwrap();
+    let next = match manager.bump(stats()).await {
+        Err(BoticordError::BumpCooldown(next)) => next,
+        other => panic!("unexpected result: {:?}", other),
+    };
+    assert_eq!(next, bumped + DEFAULT_BUMP_COOLDOWN);
+    assert_eq!(manager.last_bump_at(&server()).await, Some(bumped));
+    assert_eq!(transport.calls(), 2);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn waits_out_zero_retry_after() {
+    let transport = Scripted::new().respond(too_early("0")).reply(200, "{}");
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+    let manager = BumpManager::new(client, MemoryStore).unwrap().with_cooldown(Duration::from_secs(1));
+
+    let started = std::time::Instant::now();
+    let bump = tokio::time::timeout(Duration::from_secs(5), manager.bump_when_ready(stats())).await;
+    bump.expect("still bumping").expect("failed bump");
+    assert_eq!(transport.calls(), 2);
+    assert!(started.elapsed() >= Duration::from_millis(500));
+}
+
+#[tokio::test]
+async fn passes_other_errors_through() {
+    let transport = Scripted::replies(&[(500, "{}")]);
+    let manager = manager(&transport);
+
+    let error = manager.bump(stats()).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::INTERNAL_SERVER_ERROR));
+    assert_eq!(manager.next_bump_at(&server()).await, None);
+}
+
+#[tokio::test]
+async fn keeps_records_readable_while_bumping() {
+    let transport = Scripted::always(200, "{}").delay(Duration::from_millis(200));
+    let manager = std::sync::Arc::new(manager(&transport));
+
+    let bump = tokio::spawn({
+        let manager = manager.clone();
+        async move { manager.bump(stats()).await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let read = tokio::time::timeout(Duration::from_millis(50), manager.next_bump_at(&server())).await;
+    assert_eq!(read, Ok(None));
+
+    bump.await.unwrap().expect("failed bump");
+    assert!(manager.next_bump_at(&server()).await.is_some());
+}
+
+#[tokio::test]
+async fn persists_records() {
+    let path = std::env::temp_dir().join(format!("boticordrs-bumps-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let transport = Scripted::replies(&[(200, "{}")]);
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+
+    let manager = BumpManager::new(client.clone(), JsonFileStore::new(&path)).unwrap();
+    let next = manager.bump(stats()).await.expect("failed bump");
+    drop(manager);
+
+    // after a restart the cooldown is still known
+    let manager = BumpManager::new(client, JsonFileStore::new(&path)).unwrap();
+    assert_eq!(secs(manager.next_bump_at(&server()).await), secs(Some(next)));
+    assert!(matches!(manager.bump(stats()).await, Err(BoticordError::BumpCooldown(_))));
+    assert_eq!(transport.calls(), 1);
+
+    std::fs::remove_file(&path).unwrap();
+}
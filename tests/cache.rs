@@ -0,0 +1,189 @@
+//! In-memory caching of GET responses.
+
+use boticordrs::cache::Cache;
+use boticordrs::{BoticordClient, BoticordError};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+use common::{response, Scripted};
+
+/// Answers every request with a comment list whose length is the request number.
+fn counting() -> Scripted {
+    Scripted::new().otherwise(|_, n| {
+        let comments = vec![serde_json::json!({"userID": "1", "text": "hi", "vote": 1, "isUpdated": false, "created_at": 0, "updated_at": 0}); n];
+        Ok(response(200, serde_json::to_vec(&comments).unwrap()))
+    })
+}
+
+fn client(transport: &Scripted, cache: &Cache) -> BoticordClient {
+    BoticordClient::new_with_transport(transport.clone(), "token", 2).with_cache(cache.clone())
+}
+
+#[tokio::test]
+async fn serves_fresh_entries() {
+    let transport = counting();
+    let cache = Cache::new(10).with_ttl("/bot/{}/comments", Duration::from_secs(60));
+    let client = client(&transport, &cache);
+
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap().len(), 1);
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap().len(), 1);
+    assert_eq!(client.get_bot_comments("2".to_string()).await.unwrap().len(), 2);
+    assert_eq!(transport.calls(), 2);
+
+    // routes without a TTL are not cached
+    client.get_server_comments("1".to_string()).await.unwrap();
+    client.get_server_comments("1".to_string()).await.unwrap();
+    assert_eq!(transport.calls(), 4);
+}
+
+#[tokio::test]
+async fn invalidates_and_evicts() {
+    let transport = counting();
+    let cache = Cache::new(2).with_default_ttl(Duration::from_secs(60));
+    let client = client(&transport, &cache);
+
+    client.get_bot_comments("1".to_string()).await.unwrap();
+    client.get_server_comments("1".to_string()).await.unwrap();
+    cache.invalidate("1");
+    assert!(cache.is_empty());
+
+    client.get_bot_comments("1".to_string()).await.unwrap();
+    client.get_bot_comments("2".to_string()).await.unwrap();
+    client.get_bot_comments("1".to_string()).await.unwrap();
+    client.get_bot_comments("3".to_string()).await.unwrap();
+    assert_eq!(cache.len(), 2);
+    assert_eq!(transport.calls(), 5);
+
+    // "2" was the least recently used entry
+    client.get_bot_comments("1".to_string()).await.unwrap();
+    client.get_bot_comments("2".to_string()).await.unwrap();
+    assert_eq!(transport.calls(), 6);
+}
+
+#[tokio::test]
+async fn invalidates_by_short_code() {
+    let transport = Scripted::new().otherwise(|request, _| match request.url.ends_with("/comments") {
+        true => Ok(response(200, "[]")),
+        false => Ok(response(200, include_bytes!("fixtures/v2/bot.json").to_vec())),
+    });
+    let cache = Cache::new(10).with_default_ttl(Duration::from_secs(60));
+    let client = client(&transport, &cache);
+
+    client.get_bot_info("moon".to_string()).await.unwrap();
+    client.get_bot_comments("724663360934772797".to_string()).await.unwrap();
+    client.get_bot_comments("moon".to_string()).await.unwrap();
+    cache.invalidate("724663360934772797");
+    assert!(cache.is_empty());
+
+    client.get_bot_info("724663360934772797".to_string()).await.unwrap();
+    client.get_bot_comments("moon".to_string()).await.unwrap();
+    cache.invalidate("moon");
+    assert!(cache.is_empty());
+}
+
+#[tokio::test]
+async fn revalidates_stale_entries_in_background() {
+    let transport = counting();
+    let cache = Cache::new(10)
+        .with_ttl("/bot/{}/comments", Duration::from_millis(20))
+        .with_stale_while_revalidate(Duration::from_secs(60));
+    let client = client(&transport, &cache);
+
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap().len(), 1);
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    // stale answer right away, refreshed once in the background
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap().len(), 1);
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap().len(), 1);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap().len(), 2);
+    assert_eq!(transport.calls(), 2);
+}
+
+#[tokio::test]
+async fn serves_persisted_entries_offline() {
+    let dir = std::env::temp_dir().join(format!("boticordrs-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let open = || {
+        Cache::persistent(&dir, 10)
+            .expect("failed cache")
+            .with_ttl("/bot/{}", Duration::from_millis(10))
+            .with_offline_mode(true)
+    };
+
+    // serves the bot fixture until it is taken down
+    let down = Arc::new(AtomicBool::new(false));
+    let transport = {
+        let down = Arc::clone(&down);
+        Scripted::new().otherwise(move |_, _| match down.load(Ordering::SeqCst) {
+            true => Err(BoticordError::Transport("connection refused".into())),
+            false => Ok(response(200, include_bytes!("fixtures/v2/bot.json").to_vec())),
+        })
+    };
+    let first = open();
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2).with_cache(first.clone());
+    let fresh = client.get_bot_info_cached("1".to_string()).await.expect("failed get");
+    assert!(!fresh.stale);
+    assert_eq!(fresh.stored_at, None);
+
+    // restart while BotiCord is down
+    first.flush().expect("failed flush");
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    down.store(true, Ordering::SeqCst);
+    let cache = open();
+    assert_eq!(cache.len(), 1);
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2).with_cache(cache.clone());
+
+    let offline = client.get_bot_info_cached("1".to_string()).await.expect("failed offline get");
+    assert!(offline.stale);
+    assert!(offline.stored_at.is_some());
+    assert_eq!(offline.value, fresh.value);
+    assert_eq!(transport.calls(), 2);
+
+    // nothing known about this one
+    assert!(matches!(client.get_bot_info("2".to_string()).await, Err(BoticordError::Transport(_))));
+
+    cache.clear();
+    cache.flush().expect("failed flush");
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn revalidates_with_last_modified() {
+    let transport = Scripted::new().otherwise(|request, _| {
+        let modified = !request.headers.contains_key("if-modified-since");
+        let mut resp = response(if modified { 200 } else { 304 }, if modified { &b"[]"[..] } else { &b""[..] });
+        resp.headers.insert("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        Ok(resp)
+    });
+    let cache = Cache::new(10).with_ttl("/bot/{}/comments", Duration::from_millis(10));
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2).with_cache(cache);
+
+    assert!(client.get_bot_comments("1".to_string()).await.unwrap().is_empty());
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(client.get_bot_comments("1".to_string()).await.unwrap().is_empty());
+
+    let conditions: Vec<_> = transport.requests().iter()
+        .map(|r| r.headers.get("if-modified-since").map(|v| v.to_str().unwrap().to_string()))
+        .collect();
+    assert_eq!(conditions, [None, Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string())]);
+}
+
+#[tokio::test]
+async fn reports_failed_writes_on_flush() {
+    let dir = std::env::temp_dir().join(format!("boticordrs-cache-gone-{}", std::process::id()));
+    let cache = Cache::persistent(&dir, 10).expect("failed cache").with_default_ttl(Duration::from_secs(60));
+    std::fs::remove_dir_all(&dir).unwrap();
+    let transport = counting();
+
+    // answered even though it can't be saved
+    assert_eq!(client(&transport, &cache).get_bot_comments("1".to_string()).await.unwrap().len(), 1);
+    assert!(matches!(cache.flush(), Err(BoticordError::Store(_))));
+    cache.flush().expect("failure reported twice");
+}
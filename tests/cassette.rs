@@ -0,0 +1,84 @@
+//! Record and replay of the client's HTTP traffic.
+
+use boticordrs::cassette::{Cassette, CassetteMode};
+use boticordrs::types::BotStats;
+use boticordrs::{BoticordClient, BoticordError};
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+mod common;
+
+use common::{response, Scripted};
+
+fn path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("boticordrs-cassette-{}-{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn client(transport: Scripted, cassette: impl Into<Arc<Cassette>>) -> BoticordClient {
+    BoticordClient::new_with_transport(transport, "secret-token", 2)
+        .with_base_url("http://boticord.test")
+        .with_cassette(cassette)
+}
+
+#[tokio::test]
+async fn replays_recorded_interactions() {
+    let path = path("round-trip");
+    let transport = Scripted::replies(&[(200, "[{\"userID\":\"1\",\"text\":\"hi\",\"vote\":1,\"isUpdated\":false}]"), (200, "{}")]);
+    let cassette = Arc::new(Cassette::record(&path));
+    let recorder = client(transport, cassette.clone());
+
+    let recorded = recorder.get_bot_comments("1".to_string()).await.expect("failed get");
+    recorder.post_bot_stats(BotStats { servers: 1, shards: 1, users: 1 }).await.expect("failed post");
+    // buffered until finished
+    assert!(!path.exists());
+    cassette.finish().unwrap();
+
+    // no network is used
+    let cassette = Arc::new(Cassette::replay(&path).unwrap());
+    assert_eq!(cassette.mode(), CassetteMode::Replay);
+    let player = client(Scripted::new(), cassette.clone());
+    assert_eq!(player.get_bot_comments("1".to_string()).await.expect("not replayed"), recorded);
+    assert_eq!(cassette.unused().len(), 1);
+    player.post_bot_stats(BotStats { servers: 1, shards: 1, users: 1 }).await.expect("not replayed");
+    assert!(cassette.unused().is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn writes_on_drop_without_the_token() {
+    let path = path("redacted");
+    let transport = Scripted::new().otherwise(|request, _| {
+        let body = format!("{{\"error\":\"bad token {}\"}}", request.headers["authorization"].to_str().unwrap());
+        Ok(response(401, body))
+    });
+    let recorder = client(transport, Cassette::record(&path));
+    let _ = recorder.get_bot_comments("secret-token".to_string()).await;
+    drop(recorder);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(!text.contains("secret-token"), "{}", text);
+    assert!(text.contains("/v2/bot/[REDACTED]/comments"), "{}", text);
+    assert!(text.contains("bad token [REDACTED]"), "{}", text);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn fails_unrecorded_requests() {
+    let path = path("unmatched");
+    let cassette = Cassette::record(&path);
+    cassette.finish().unwrap();
+
+    let player = client(Scripted::new(), Cassette::replay(&path).unwrap());
+    match player.get_bot_comments("1".to_string()).await {
+        Err(BoticordError::Cassette(e)) => assert_eq!(e, "no recorded interaction for GET /v2/bot/1/comments"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(matches!(Cassette::replay(path.with_extension("missing")), Err(BoticordError::Cassette(_))));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,111 @@
+//! Sharing of identical concurrent GET requests.
+
+use boticordrs::options::RequestOptions;
+use boticordrs::retry::RetryPolicy;
+use boticordrs::{BoticordClient, BoticordError};
+use http::StatusCode;
+
+use std::time::Duration;
+
+mod common;
+
+use common::{response, Scripted};
+
+/// Answers after a delay, so concurrent requests overlap.
+fn slow(status: u16) -> Scripted {
+    Scripted::always(status, "[]").delay(Duration::from_millis(20))
+}
+
+#[tokio::test]
+async fn shares_identical_requests() {
+    let transport = slow(200);
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+
+    let (a, b, c, other) = tokio::join!(
+        client.get_bot_comments("1".to_string()),
+        client.get_bot_comments("1".to_string()),
+        client.get_bot_comments("1".to_string()),
+        client.get_bot_comments("2".to_string()),
+    );
+    assert!(a.is_ok() && b.is_ok() && c.is_ok() && other.is_ok());
+    assert_eq!(transport.calls(), 2);
+
+    // another token is another request
+    let other_bot = client.for_token("other");
+    let (a, b) = tokio::join!(client.get_bot_comments("1".to_string()), other_bot.get_bot_comments("1".to_string()));
+    assert!(a.is_ok() && b.is_ok());
+    assert_eq!(transport.calls(), 4);
+}
+
+#[tokio::test]
+async fn shares_errors() {
+    let transport = slow(503);
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+
+    let (a, b) = tokio::join!(client.get_bot_comments("1".to_string()), client.get_bot_comments("1".to_string()));
+    assert_eq!(a.unwrap_err().status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(b.unwrap_err().status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(transport.calls(), 1);
+}
+
+#[tokio::test]
+async fn can_be_disabled() {
+    let transport = slow(200);
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2).with_coalescing(false);
+
+    let (a, b) = tokio::join!(client.get_bot_comments("1".to_string()), client.get_bot_comments("1".to_string()));
+    assert!(a.is_ok() && b.is_ok());
+    assert_eq!(transport.calls(), 2);
+}
+
+#[tokio::test]
+async fn survives_cancelled_leader() {
+    let transport = slow(200);
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+
+    let leader = client.get_bot_comments("1".to_string());
+    let follower = client.get_bot_comments("1".to_string());
+    tokio::pin!(follower);
+    tokio::select! {
+        biased;
+        _ = leader => panic!("leader finished"),
+        _ = &mut follower => panic!("follower finished"),
+        _ = tokio::time::sleep(Duration::from_millis(5)) => {}
+    }
+
+    // the leader is dropped, the follower makes its own request
+    assert!(follower.await.is_ok());
+    assert_eq!(transport.calls(), 2);
+}
+
+#[tokio::test]
+async fn keeps_timeouts_to_their_callers() {
+    let transport = slow(200);
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+
+    let short = RequestOptions::new().timeout(Duration::from_millis(5));
+    let (leader, follower) = tokio::join!(
+        client.with_options(short, |client| client.get_bot_comments("1".to_string())),
+        client.get_bot_comments("1".to_string()),
+    );
+    assert!(matches!(leader, Err(BoticordError::Timeout(_))), "{:?}", leader);
+    assert!(follower.is_ok(), "{:?}", follower);
+}
+
+#[tokio::test]
+async fn shares_requests_only_with_the_same_retry_policy() {
+    let transport = Scripted::new().reply(503, "").otherwise(|_, _| Ok(response(200, "[]"))).delay(Duration::from_millis(20));
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+
+    let retry = RetryPolicy { max_retries: 1, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(1), ..RetryPolicy::none() };
+    let (retried, plain) = tokio::join!(
+        client.with_options(RequestOptions::new().retry(retry), |client| client.get_bot_comments("1".to_string())),
+        async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            client.get_bot_comments("1".to_string()).await
+        },
+    );
+    // the plain call made its own request and was not retried
+    assert!(retried.is_ok() && plain.is_ok());
+    assert_eq!(transport.calls(), 3);
+}
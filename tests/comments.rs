@@ -0,0 +1,94 @@
+//! Comments in chunks, sorted and filtered.
+
+use boticordrs::comments::{CommentOrder, CommentQuery};
+use boticordrs::types::{BotId, ServerId};
+use boticordrs::watcher::CommentTarget;
+use boticordrs::BoticordClient;
+use futures_util::StreamExt;
+use http::StatusCode;
+use serde_json::json;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+mod common;
+
+use common::Scripted;
+
+fn comment(user: &str, vote: u64, created_at: Option<u64>) -> serde_json::Value {
+    json!({"userID": user, "text": "hi", "vote": vote, "isUpdated": false, "created_at": created_at, "updated_at": null})
+}
+
+fn client(body: Option<serde_json::Value>) -> BoticordClient {
+    // a missing body is a missing bot or server
+    let transport = match body {
+        Some(body) => Scripted::always(200, body.to_string()),
+        None => Scripted::always(404, ""),
+    };
+    BoticordClient::new_with_transport(transport, "token", 2)
+}
+
+#[tokio::test]
+async fn chunks_comments() {
+    let comments: Vec<_> = (0..5).map(|n| comment(&n.to_string(), 1, Some(n))).collect();
+    let client = client(Some(json!(comments)));
+
+    let chunks: Vec<_> = client
+        .get_bot_comments_stream("1".to_string(), CommentQuery::new().chunk_size(2))
+        .map(|chunk| chunk.unwrap().iter().map(|c| c.user_id.clone()).collect::<Vec<_>>())
+        .collect()
+        .await;
+    assert_eq!(chunks, vec![vec!["0", "1"], vec!["2", "3"], vec!["4"]]);
+}
+
+#[tokio::test]
+async fn sorts_and_filters() {
+    let client = client(Some(json!([
+        comment("a", 1, Some(1_000)),
+        comment("b", 0, Some(3_000)),
+        comment("a", 1, None),
+        comment("a", 1, Some(2_000)),
+    ])));
+    let dates = |page: Vec<boticordrs::types::SingleComment>| page.into_iter().map(|c| c.created_at).collect::<Vec<_>>();
+
+    let newest = CommentQuery::new().order(CommentOrder::Newest).author("a").vote(1);
+    let page = client.get_server_comments_stream("1".to_string(), newest).next().await.unwrap().unwrap();
+    assert_eq!(dates(page), vec![Some(2_000), Some(1_000), None]);
+
+    let window = CommentQuery::new()
+        .order(CommentOrder::LowestVote)
+        .since(UNIX_EPOCH + Duration::from_secs(2))
+        .until(UNIX_EPOCH + Duration::from_secs(4));
+    let page = client.get_server_comments_stream("1".to_string(), window).next().await.unwrap().unwrap();
+    assert_eq!(dates(page), vec![Some(3_000), Some(2_000)]);
+}
+
+#[tokio::test]
+async fn streams_user_comments_and_errors() {
+    let mut on_bot = comment("1", 1, Some(1));
+    on_bot["botID"] = json!("10");
+    let mut on_server = comment("1", 1, Some(2));
+    on_server["serverID"] = json!("20");
+    let client_with_comments = client(Some(json!({
+        "bots": [on_bot, comment("1", 1, Some(3))],
+        "servers": [on_server],
+    })));
+    let chunks: Vec<_> = client_with_comments
+        .get_user_comments_stream("1".to_string(), CommentQuery::new().order(CommentOrder::Oldest))
+        .collect()
+        .await;
+    assert_eq!(chunks.len(), 1);
+    let targets: Vec<_> = chunks[0].as_ref().unwrap().iter().map(|c| (c.target.clone(), c.comment.created_at)).collect();
+    assert_eq!(targets, vec![
+        (CommentTarget::Bot(BotId("10".to_string())), Some(1)),
+        (CommentTarget::Server(ServerId("20".to_string())), Some(2)),
+        (CommentTarget::Bot(BotId(String::new())), Some(3)),
+    ]);
+
+    let missing = client(None);
+    let chunks: Vec<_> = missing.get_bot_comments_stream("1".to_string(), CommentQuery::new()).collect().await;
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].as_ref().unwrap_err().status(), Some(StatusCode::NOT_FOUND));
+
+    let empty = client(Some(json!([])));
+    assert!(empty.get_bot_comments_stream("1".to_string(), CommentQuery::new()).next().await.is_none());
+}
@@ -0,0 +1,116 @@
+//! Scripted transport shared by the integration tests.
+#![allow(dead_code)]
+
+use boticordrs::transport::{BoxFuture, HttpRequest, HttpResponse, HttpTransport};
+use boticordrs::BoticordError;
+use http::{HeaderMap, StatusCode};
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Answer = Result<HttpResponse, BoticordError>;
+type Responder = Arc<dyn Fn(&HttpRequest, usize) -> Answer + Send + Sync>;
+
+/// Response with the status and body, without headers.
+pub fn response(status: u16, body: impl Into<Vec<u8>>) -> HttpResponse {
+    HttpResponse { status: StatusCode::from_u16(status).unwrap(), headers: HeaderMap::new(), body: body.into() }
+}
+
+/// Transport answering from a script, clones share it.
+///
+/// Queued answers come first, then the responder is asked. Without
+/// a responder an empty script fails with a transport error.
+#[derive(Clone, Default)]
+pub struct Scripted {
+    queue: Arc<Mutex<VecDeque<Answer>>>,
+    responder: Option<Responder>,
+    delay: Duration,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+}
+
+impl Scripted {
+    pub fn new() -> Self {
+        Scripted::default()
+    }
+
+    /// Answers with the statuses and bodies in order.
+    pub fn replies(replies: &[(u16, &str)]) -> Self {
+        replies.iter().fold(Scripted::new(), |script, (status, body)| script.reply(*status, *body))
+    }
+
+    /// Answers every request with the status and body.
+    pub fn always(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Scripted::always_respond(response(status, body))
+    }
+
+    /// Answers every request with the response.
+    pub fn always_respond(response: HttpResponse) -> Self {
+        Scripted::new().otherwise(move |_, _| Ok(response.clone()))
+    }
+
+    /// Queues a response with the status and body.
+    pub fn reply(self, status: u16, body: impl Into<Vec<u8>>) -> Self {
+        self.respond(response(status, body))
+    }
+
+    /// Queues the response.
+    pub fn respond(self, response: HttpResponse) -> Self {
+        self.queue.lock().unwrap().push_back(Ok(response));
+        self
+    }
+
+    /// Queues a transport error.
+    pub fn fail(self, message: &str) -> Self {
+        self.queue.lock().unwrap().push_back(Err(BoticordError::Transport(message.to_string().into())));
+        self
+    }
+
+    /// Answers requests once the queue is empty, with the request and its number from `1`.
+    pub fn otherwise<F>(mut self, responder: F) -> Self
+        where
+            F: Fn(&HttpRequest, usize) -> Answer + Send + Sync + 'static,
+    {
+        self.responder = Some(Arc::new(responder));
+        self
+    }
+
+    /// Waits before every answer.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Requests sent so far.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Number of requests sent so far.
+    pub fn calls(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+}
+
+impl HttpTransport for Scripted {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoticordError>> {
+        let number = {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(request.clone());
+            requests.len()
+        };
+        let answer = match self.queue.lock().unwrap().pop_front() {
+            Some(answer) => answer,
+            None => match &self.responder {
+                Some(responder) => responder(&request, number),
+                None => Err(BoticordError::Transport("script is over".into())),
+            },
+        };
+        Box::pin(async move {
+            if !self.delay.is_zero() {
+                tokio::time::sleep(self.delay).await;
+            }
+            answer
+        })
+    }
+}
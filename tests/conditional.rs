@@ -0,0 +1,74 @@
+//! Conditional revalidation of cached responses against the mock server.
+#![cfg(feature = "mock")]
+
+use boticordrs::cache::Cache;
+use boticordrs::mock::{MockServer, DEFAULT_MOCK_TOKEN};
+use boticordrs::transport::{BoxFuture, HttpRequest, HttpResponse, HttpTransport};
+use boticordrs::{BoticordClient, BoticordError};
+use serde_json::Value;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// `If-None-Match` of a request and status of its response.
+type Exchange = (Option<String>, u16);
+
+/// Sends requests with reqwest and records what went over the wire.
+#[derive(Clone, Default)]
+struct Recording {
+    client: reqwest::Client,
+    exchanges: Arc<Mutex<Vec<Exchange>>>,
+}
+
+impl Recording {
+    fn exchanges(&self) -> Vec<Exchange> {
+        self.exchanges.lock().unwrap().clone()
+    }
+}
+
+impl HttpTransport for Recording {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BoticordError>> {
+        Box::pin(async move {
+            let condition = request.headers.get("if-none-match").map(|v| v.to_str().unwrap().to_string());
+            let resp = self.client.send(request).await?;
+            self.exchanges.lock().unwrap().push((condition, resp.status.as_u16()));
+            Ok(resp)
+        })
+    }
+}
+
+fn comments(text: &str) -> Value {
+    serde_json::json!([{ "userID": "1", "text": text, "vote": 1, "isUpdated": false }])
+}
+
+#[tokio::test]
+async fn revalidates_with_etag() {
+    let server = MockServer::builder()
+        .fixture("/bot/1/comments", comments("first"))
+        .start()
+        .await
+        .expect("failed server");
+    let transport = Recording::default();
+    let cache = Cache::new(10).with_ttl("/bot/{}/comments", Duration::from_millis(10));
+    let client = BoticordClient::new_with_transport(transport.clone(), DEFAULT_MOCK_TOKEN, 2)
+        .with_base_url(server.url())
+        .with_cache(cache);
+
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap()[0].text, "first");
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap()[0].text, "first");
+
+    // renewed by the 304, so served from the cache
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap()[0].text, "first");
+
+    server.set_fixture("/bot/1/comments", comments("second"));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap()[0].text, "second");
+
+    let exchanges = transport.exchanges();
+    assert_eq!(exchanges.len(), 3);
+    assert_eq!(exchanges[0], (None, 200));
+    let etag = exchanges[1].0.clone().expect("no If-None-Match");
+    assert_eq!(exchanges[1].1, 304);
+    assert_eq!(exchanges[2], (Some(etag), 200));
+}
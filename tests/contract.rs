@@ -0,0 +1,288 @@
+//! Contract tests: every model must decode the responses in `tests/fixtures`
+//! and survive a serialize/deserialize round trip.
+//!
+//! Fixtures cover API v1 and v2, the versions the client supports. API v3
+//! wraps every response in a `result` envelope and renames or restructures
+//! most fields, so the client rejects it (see `token::VERSIONS`) until v3
+//! models and fixtures are added together.
+
+use boticordrs::types::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_test::{assert_de_tokens, assert_tokens, Token};
+
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> String {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "fixtures", &format!("{}.json", name)]
+        .iter()
+        .collect();
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+/// Decodes the fixture and checks that the model round-trips.
+fn check<T>(name: &str) -> T
+    where
+        T: DeserializeOwned + Serialize + PartialEq + Debug,
+{
+    let decoded: T = serde_json::from_str(&fixture(name))
+        .unwrap_or_else(|e| panic!("{} failed to decode: {}", name, e));
+    let encoded = serde_json::to_string(&decoded).expect("failed to encode");
+    let again: T = serde_json::from_str(&encoded)
+        .unwrap_or_else(|e| panic!("{} failed to decode after round trip: {}", name, e));
+    assert_eq!(decoded, again, "{} changed after round trip", name);
+    decoded
+}
+
+fn check_invalid<T: DeserializeOwned + Debug>(name: &str) {
+    let result = serde_json::from_str::<T>(&fixture(name));
+    assert!(result.is_err(), "{} decoded: {:?}", name, result);
+}
+
+#[test]
+fn bot() {
+    let bot: Bot = check("v2/bot");
+    assert_eq!(bot.id, BotId("724663360934772797".to_string()));
+    assert_eq!(bot.information.developers.len(), 2);
+    assert_eq!(bot.information.stats.servers, 2514);
+    let links = &bot.information.links;
+    assert_eq!(links.discord.as_ref().map(SocialLink::as_str), Some("https://discord.gg/hkHjW8a"));
+    assert_eq!(links.site.as_ref().map(SocialLink::as_str), Some("https://moonbot.example.com/"));
+
+    let bot: Bot = check("v2/bot_nulls");
+    assert!(bot.server.is_none());
+    assert!(bot.information.links.discord.is_none());
+
+    let bot: Bot = check("v2/bot_minimal");
+    assert!(bot.short_code.is_none());
+    assert!(bot.information.library.is_none());
+
+    let bot: Bot = check("v2/bot_huge_numbers");
+    assert_eq!(bot.information.permissions, u64::MAX);
+    assert_eq!(bot.information.stats.users, u64::MAX);
+    assert!(bot.information.links.discord.is_none());
+    assert_eq!(
+        bot.information.links.github.as_ref().map(SocialLink::as_str),
+        Some("https://github.com/grey-cat-1908/boticordrs")
+    );
+    assert_eq!(bot.information.links.site, Some(SocialLink::Raw("not a url".to_string())));
+
+    let bot: Bot = check("v1/bot");
+    assert!(bot.links.is_none());
+
+    check_invalid::<Bot>("invalid/bot_missing_information");
+}
+
+#[test]
+fn server() {
+    let server: Server = check("v2/server");
+    assert_eq!(server.information.members, Some(MemberCounts { total: 1543, online: 287 }));
+    assert_eq!(server.information.owner, Some(UserId("585766846268047370".to_string())));
+    let links = server.information.links.expect("links");
+    assert_eq!(links.invite.map(|l| l.to_string()), Some("https://discord.gg/moonhouse".to_string()));
+    assert_eq!(links.youtube.map(|l| l.to_string()), Some("https://www.youtube.com/@moonhouse".to_string()));
+    assert_eq!(links.twitch.map(|l| l.to_string()), Some("https://www.twitch.tv/moonhouse_tv".to_string()));
+    assert_eq!(
+        links.steam.map(|l| l.to_string()),
+        Some("https://steamcommunity.com/profiles/76561198000000000".to_string())
+    );
+    assert_eq!(links.vk.map(|l| l.to_string()), Some("https://vk.com/moonhouse".to_string()));
+
+    let server: Server = check("v2/server_nulls");
+    assert!(server.information.members.is_none());
+    assert!(server.information.links.is_none());
+
+    let server: Server = check("v2/server_empty_links");
+    assert!(server.information.members.is_none());
+    let links = server.information.links.expect("links");
+    assert!(links.invite.is_none() && links.site.is_none() && links.vk.is_none());
+
+    let server: Server = check("v2/server_huge_numbers");
+    assert_eq!(server.information.members, Some(MemberCounts { total: u64::MAX, online: u64::MAX }));
+
+    check::<Server>("v1/server");
+
+    check_invalid::<Server>("invalid/server_members_not_numbers");
+    check_invalid::<Server>("invalid/server_members_wrong_length");
+    check_invalid::<Server>("invalid/server_members_too_long");
+}
+
+#[test]
+fn user_information() {
+    let user: UserInformation = check("v2/profile");
+    assert_eq!(user.git.map(|l| l.to_string()), Some("https://github.com/grey-cat-1908".to_string()));
+    assert_eq!(
+        user.youtube.map(|l| l.to_string()),
+        Some("https://www.youtube.com/channel/UCxxxxxxxxxxxxxxxxxxxxxx".to_string())
+    );
+    assert_eq!(user.steam.map(|l| l.to_string()), Some("https://steamcommunity.com/id/marakarka".to_string()));
+
+    let user: UserInformation = check("v2/profile_nulls");
+    assert!(user.status.is_none() && user.site.is_none());
+
+    let user: UserInformation = check("v2/profile_minimal");
+    assert!(user.short_code.is_none() && user.git.is_none());
+
+    let user: UserInformation = check("v1/profile");
+    assert!(user.site.is_none());
+}
+
+#[test]
+fn comments() {
+    let comments: Vec<SingleComment> = check("v2/comments");
+    assert_eq!(comments.len(), 2);
+    assert!(comments[1].is_updated);
+    assert_eq!(comments[1].updated_at, Some(1643950000000));
+
+    assert!(check::<Vec<SingleComment>>("v2/comments_empty").is_empty());
+
+    let comments: Vec<SingleComment> = check("v2/comments_huge_numbers");
+    assert_eq!(comments[0].vote, u64::MAX);
+
+    let comments: Vec<SingleComment> = check("v1/comments");
+    assert!(comments[0].created_at.is_none());
+
+    check_invalid::<Vec<SingleComment>>("invalid/comments_not_array");
+}
+
+#[test]
+fn user_comments() {
+    let comments: UserComments = check("v2/user_comments");
+    assert_eq!(comments.bots.map(|c| c.len()), Some(2));
+
+    let comments: UserComments = check("v2/user_comments_nulls");
+    assert!(comments.bots.is_none() && comments.servers.is_none());
+
+    let comments: UserComments = check("v2/user_comments_empty");
+    assert_eq!(comments.servers, Some(Vec::new()));
+
+    let comments: UserComments = check("v1/user_comments");
+    assert!(comments.servers.is_none());
+}
+
+#[test]
+fn user_bots() {
+    let bots: Vec<SingleUserBot> = check("v2/user_bots");
+    assert_eq!(bots[0].short_code.as_deref(), Some("moon"));
+    assert!(check::<Vec<SingleUserBot>>("v2/user_bots_empty").is_empty());
+    check::<Vec<SingleUserBot>>("v1/user_bots");
+}
+
+#[test]
+fn shorted_links() {
+    let links: Vec<ShortedLink> = check("v2/shorted_links");
+    assert_eq!(links[1].domain, LinkDomain::DiscordCamp.host());
+    assert!(check::<Vec<ShortedLink>>("v2/shorted_links_empty").is_empty());
+
+    let link: ShortedLink = check("v2/shorted_link");
+    assert_eq!(link.owner_id, UserId("585766846268047370".to_string()));
+
+    let link: ShortedLink = check("v2/shorted_link_huge_numbers");
+    assert_eq!(link.id, u64::MAX);
+}
+
+#[test]
+fn bot_stats() {
+    let stats: BotStats = check("v2/bot_stats");
+    assert_eq!(stats, BotStats { servers: 2514, shards: 3, users: 338250 });
+    let stats: BotStats = check("v2/bot_stats_huge_numbers");
+    assert_eq!(stats.servers, u64::MAX);
+    check::<BotStats>("v1/bot_stats");
+
+    check_invalid::<BotStats>("invalid/bot_stats_overflow");
+    check_invalid::<BotStats>("invalid/bot_stats_negative");
+}
+
+#[test]
+fn server_stats() {
+    let stats: ServerStats = check("v2/server_stats");
+    let expected = ServerStats::builder(ServerId("724657775652634795".to_string()))
+        .bump(true)
+        .name("Moon House")
+        .members(1543, 287)
+        .owner(UserId("585766846268047370".to_string()))
+        .build()
+        .expect("valid stats");
+    assert_eq!(stats, expected);
+
+    let stats: ServerStats = check("v2/server_stats_nulls");
+    assert!(!stats.up && !stats.status);
+    assert!(stats.members().is_none());
+}
+
+#[test]
+fn server_stats_wire_format() {
+    let stats = ServerStats::builder(ServerId("1".to_string()))
+        .bump(true)
+        .members(10, 3)
+        .build()
+        .expect("valid stats");
+    let value = serde_json::to_value(&stats).expect("failed to encode");
+    assert_eq!(value, serde_json::json!({
+        "serverID": "1",
+        "up": 1,
+        "status": 1,
+        "serverName": null,
+        "serverAvatar": null,
+        "serverMembersAllCount": 10,
+        "serverMembersOnlineCount": 3,
+        "serverOwnerID": null,
+    }));
+
+    let invalid = ServerStats::builder(ServerId("1".to_string())).members(3, 10).build();
+    assert!(invalid.is_err());
+    let invalid = serde_json::json!({ "serverID": "1", "up": 0, "status": 1, "serverMembersAllCount": 3, "serverMembersOnlineCount": 10 });
+    assert!(serde_json::from_value::<ServerStats>(invalid).is_err());
+}
+
+#[test]
+fn server_stats_single_member_count() {
+    let online = ServerStats::builder(ServerId("1".to_string())).online_members(3).build().expect("valid stats");
+    assert_eq!((online.total_members(), online.online_members(), online.members()), (None, Some(3), None));
+    let value = serde_json::to_value(&online).expect("failed to encode");
+    assert_eq!((&value["serverMembersAllCount"], &value["serverMembersOnlineCount"]), (&serde_json::Value::Null, &serde_json::json!(3)));
+    assert_eq!(serde_json::from_value::<ServerStats>(value).expect("failed to decode"), online);
+
+    let total = ServerStats::builder(ServerId("1".to_string())).total_members(10).build().expect("valid stats");
+    let value = serde_json::to_value(&total).expect("failed to encode");
+    assert_eq!((&value["serverMembersAllCount"], &value["serverMembersOnlineCount"]), (&serde_json::json!(10), &serde_json::Value::Null));
+    assert_eq!(serde_json::from_value::<ServerStats>(value).expect("failed to decode"), total);
+}
+
+#[test]
+fn request_bodies() {
+    let body: ShortenerBody = check("v2/shortener_body");
+    assert_eq!(body.domain, Some(LinkDomain::BCordCC));
+    let body: ShortenerBody = check("v2/shortener_body_nulls");
+    assert!(body.code.is_none() && body.link.is_none() && body.domain.is_none());
+    check::<EmptyBody>("v2/empty_body");
+}
+
+#[test]
+fn member_counts_tokens() {
+    assert_tokens(&MemberCounts { total: 10, online: 3 }, &[
+        Token::Tuple { len: 2 },
+        Token::U64(10),
+        Token::U64(3),
+        Token::TupleEnd,
+    ]);
+    assert_de_tokens(&MemberCounts { total: 10, online: 3 }, &[
+        Token::Seq { len: Some(2) },
+        Token::U64(10),
+        Token::U64(3),
+        Token::SeqEnd,
+    ]);
+    for counts in [&[][..], &[10], &[10, 3, 1]] {
+        let error = serde_json::from_value::<MemberCounts>(serde_json::json!(counts)).unwrap_err();
+        assert!(error.to_string().contains("a [total, online] array"), "{}", error);
+    }
+}
+
+#[test]
+fn ids_tokens() {
+    assert_tokens(&BotId("1".to_string()), &[
+        Token::NewtypeStruct { name: "BotId" },
+        Token::Str("1"),
+    ]);
+}
@@ -0,0 +1,43 @@
+//! Normalization of social links.
+
+use boticordrs::types::{Platform, SocialLink};
+
+fn url(platform: Platform, value: &str) -> String {
+    match platform.normalize(value) {
+        SocialLink::Url(url) => url.to_string(),
+        SocialLink::Raw(raw) => panic!("{:?} {} kept raw: {}", platform, value, raw),
+    }
+}
+
+#[test]
+fn builds_urls_from_handles() {
+    assert_eq!(url(Platform::Discord, "hkHjW8a"), "https://discord.gg/hkHjW8a");
+    assert_eq!(url(Platform::Github, "@grey-cat-1908"), "https://github.com/grey-cat-1908");
+    assert_eq!(url(Platform::Youtube, "UCxxxxxxxxxxxxxxxxxxxxxx"), "https://www.youtube.com/channel/UCxxxxxxxxxxxxxxxxxxxxxx");
+    assert_eq!(url(Platform::Steam, "76561198000000000"), "https://steamcommunity.com/profiles/76561198000000000");
+    assert_eq!(url(Platform::Steam, "marakarka"), "https://steamcommunity.com/id/marakarka");
+}
+
+#[test]
+fn keeps_dots_in_handles() {
+    assert_eq!(url(Platform::Youtube, "@some.channel"), "https://www.youtube.com/@some.channel");
+    assert_eq!(url(Platform::Vk, "ivan.petrov"), "https://vk.com/ivan.petrov");
+    assert_eq!(url(Platform::Twitch, "@moon.house"), "https://www.twitch.tv/moon.house");
+}
+
+#[test]
+fn completes_urls_without_scheme() {
+    assert_eq!(url(Platform::Site, "moonbot.example.com"), "https://moonbot.example.com/");
+    assert_eq!(url(Platform::Youtube, "youtube.com/@moonhouse"), "https://youtube.com/@moonhouse");
+    assert_eq!(url(Platform::Vk, "m.vk.com"), "https://m.vk.com/");
+    assert_eq!(url(Platform::Vk, "@VK.com/moonhouse"), "https://vk.com/moonhouse");
+    assert_eq!(url(Platform::Discord, "discord.gg/hkHjW8a"), "https://discord.gg/hkHjW8a");
+    assert_eq!(url(Platform::Github, "example.org/moon"), "https://example.org/moon");
+}
+
+#[test]
+fn keeps_unusable_values_raw() {
+    assert_eq!(Platform::Site.normalize("not a url"), SocialLink::Raw("not a url".to_string()));
+    assert_eq!(Platform::Site.normalize("moonhouse"), SocialLink::Raw("moonhouse".to_string()));
+    assert_eq!(Platform::Vk.normalize("@"), SocialLink::Raw("@".to_string()));
+}
@@ -0,0 +1,53 @@
+//! Metrics recorded for API calls.
+#![cfg(feature = "metrics")]
+
+use boticordrs::metrics::Metrics;
+use boticordrs::types::BotStats;
+use boticordrs::BoticordClient;
+
+mod common;
+
+use common::Scripted;
+
+fn client(responses: &[(u16, &'static str)], metrics: &Metrics) -> BoticordClient {
+    let transport = Scripted::replies(responses);
+    BoticordClient::new_with_transport(transport, "token".to_string(), 2).with_metrics(metrics.clone())
+}
+
+#[tokio::test]
+async fn counts_requests_and_errors() {
+    let metrics = Metrics::new();
+    let client = client(&[(200, "[]"), (404, "{}"), (200, "not json")], &metrics);
+
+    client.get_bot_comments("1".to_string()).await.expect("failed get");
+    let _ = client.get_bot_comments("2".to_string()).await;
+    let _ = client.get_bot_comments("3".to_string()).await;
+    let _ = client.get_user_bots("1".to_string()).await;
+
+    assert_eq!(metrics.requests("/bot/{}/comments"), 3);
+    assert_eq!(metrics.requests("/bots/{}"), 1);
+    assert_eq!(metrics.errors("api"), 1);
+    assert_eq!(metrics.errors("decode"), 1);
+    assert_eq!(metrics.errors("transport"), 1);
+
+    let text = metrics.render();
+    assert!(text.contains("boticord_requests_total{route=\"/bot/{}/comments\",method=\"GET\",status=\"200\"} 2"), "{}", text);
+    assert!(text.contains("boticord_requests_total{route=\"/bot/{}/comments\",method=\"GET\",status=\"404\"} 1"), "{}", text);
+    assert!(text.contains("boticord_request_duration_seconds_count{route=\"/bot/{}/comments\"} 3"), "{}", text);
+    assert!(text.contains("boticord_request_duration_seconds_bucket{route=\"/bots/{}\",le=\"+Inf\"} 1"), "{}", text);
+    assert!(text.contains("boticord_errors_total{route=\"/bots/{}\",kind=\"transport\"} 1"), "{}", text);
+}
+
+#[tokio::test]
+async fn mirrors_posted_bot_stats() {
+    let metrics = Metrics::new();
+    let client = client(&[(200, "{}"), (500, "")], &metrics);
+
+    client.post_bot_stats(BotStats { servers: 10, shards: 2, users: 300 }).await.expect("failed post");
+    let _ = client.post_bot_stats(BotStats { servers: 11, shards: 2, users: 301 }).await;
+
+    assert_eq!(metrics.bot_stats(), Some(BotStats { servers: 10, shards: 2, users: 300 }));
+    let text = metrics.render();
+    assert!(text.contains("# TYPE boticord_bot_servers gauge\nboticord_bot_servers 10\n"), "{}", text);
+    assert!(text.contains("boticord_bot_users 300\n"), "{}", text);
+}
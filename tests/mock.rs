@@ -0,0 +1,206 @@
+//! Mock server and its standalone binary.
+#![cfg(feature = "mock")]
+
+use boticordrs::mock::{Failure, MockServer, Scenario, ScenarioStep, ADMIN_PREFIX};
+use boticordrs::types::{BotStats, ShortedLink};
+use boticordrs::{BoticordClient, BoticordError};
+use http::StatusCode;
+use serde_json::{json, Value};
+
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+fn stats() -> BotStats {
+    BotStats { servers: 1, shards: 1, users: 1 }
+}
+
+fn comments() -> Value {
+    json!([{ "userID": "1", "text": "hi", "vote": 1, "isUpdated": false }])
+}
+
+/// Directory with a bot served for every version, v1 comments and links.
+fn fixtures(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boticordrs-mock-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("bot/1")).unwrap();
+    std::fs::create_dir_all(dir.join("v1/bot/1")).unwrap();
+    std::fs::write(dir.join("bot/1/comments.json"), comments().to_string()).unwrap();
+    std::fs::write(dir.join("v1/bot/1/comments.json"), "[]").unwrap();
+    std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+    std::fs::copy("tests/fixtures/v2/shorted_links.json", dir.join("links.json")).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn rejects_wrong_tokens() {
+    let server = MockServer::builder().token("secret").start().await.expect("failed server");
+
+    server.client(2).post_bot_stats(stats()).await.expect("failed post");
+    let requests = server.requests();
+    assert_eq!((requests[0].method.as_str(), requests[0].path.as_str()), ("POST", "/v2/stats"));
+
+    let stranger = BoticordClient::new("other", 2).unwrap().with_base_url(server.url());
+    let error = stranger.post_bot_stats(stats()).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+    assert_eq!(server.bot_stats().len(), 1);
+}
+
+#[tokio::test]
+async fn injects_failures_and_latency() {
+    let server = MockServer::builder()
+        .fixture("/bot/1/comments", comments())
+        .start()
+        .await
+        .expect("failed server");
+    let client = server.client(2);
+
+    server.fail_route("/stats", Failure::status(429).with_retry_after(7));
+    server.fail_next(Failure::status(500).with_body(json!({ "error": "boom" })));
+    // failures are ordered, requests to other routes pass until the stats one is used
+    client.get_bot_comments("1".to_string()).await.expect("failed get");
+    match client.post_bot_stats(stats()).await {
+        Err(BoticordError::Api(e)) => assert_eq!((e.status.as_u16(), e.retry_after), (429, Some(Duration::from_secs(7)))),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match client.get_bot_comments("1".to_string()).await {
+        Err(BoticordError::Api(e)) => assert_eq!((e.status.as_u16(), e.body.as_str()), (500, "{\"error\":\"boom\"}")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    server.set_latency(Duration::from_millis(100));
+    let started = Instant::now();
+    client.get_bot_comments("1".to_string()).await.expect("failed get");
+    assert!(started.elapsed() >= Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn plays_scenarios() {
+    let scenario = Scenario {
+        steps: vec![
+            ScenarioStep { route: Some("/stats".to_string()), status: 429, body: None, retry_after: Some(1), times: 1 },
+            ScenarioStep { route: None, status: 503, body: None, retry_after: None, times: 2 },
+        ],
+    };
+    let server = MockServer::builder().scenario(&scenario).start().await.expect("failed server");
+    let client = server.client(2);
+
+    let statuses = [
+        client.post_bot_stats(stats()).await.unwrap_err().status(),
+        client.post_bot_stats(stats()).await.unwrap_err().status(),
+        client.post_bot_stats(stats()).await.unwrap_err().status(),
+    ];
+    assert_eq!(statuses.map(|s| s.unwrap().as_u16()), [429, 503, 503]);
+    client.post_bot_stats(stats()).await.expect("failed post");
+
+    // loaded from a file too
+    let path = std::env::temp_dir().join(format!("boticordrs-scenario-{}.json", std::process::id()));
+    std::fs::write(&path, r#"{ "steps": [{ "status": 500, "times": 2 }] }"#).unwrap();
+    let loaded = Scenario::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.steps[0].times, 2);
+    server.load_scenario(&loaded);
+    assert_eq!(client.post_bot_stats(stats()).await.unwrap_err().status(), Some(StatusCode::INTERNAL_SERVER_ERROR));
+}
+
+#[tokio::test]
+async fn serves_admin_routes() {
+    let server = MockServer::builder().token("secret").start().await.expect("failed server");
+    server.client(2).post_bot_stats(stats()).await.expect("failed post");
+    let http = reqwest::Client::new();
+    let admin = |route: &str| format!("{}{}{}", server.url(), ADMIN_PREFIX, route);
+    let get = |route: &str| {
+        let request = http.get(admin(route));
+        async move { serde_json::from_str::<Value>(&request.send().await.unwrap().text().await.unwrap()).unwrap() }
+    };
+
+    // no token is needed
+    let requests = get("/requests").await;
+    assert_eq!(requests[0]["path"], "/v2/stats");
+    let posted = get("/stats").await;
+    assert_eq!(posted["bots"][0]["servers"], 1);
+    assert_eq!(posted["servers"], json!([]));
+
+    let scenario = json!({ "steps": [{ "status": 502 }] });
+    let resp = http.post(admin("/scenario")).body(scenario.to_string()).send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(server.client(2).post_bot_stats(stats()).await.unwrap_err().status(), Some(StatusCode::BAD_GATEWAY));
+    let resp = http.post(admin("/scenario")).body("{\"steps\":1}").send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = http.post(admin("/reset")).send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(server.requests().is_empty() && server.bot_stats().is_empty());
+    assert_eq!(http.get(admin("/nothing")).send().await.unwrap().status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn loads_fixtures_dir() {
+    let dir = fixtures("dir");
+    let server = MockServer::builder().fixtures_dir(&dir).unwrap().start().await.expect("failed server");
+
+    assert_eq!(server.client(2).get_bot_comments("1".to_string()).await.unwrap().len(), 1);
+    // versioned fixtures override the shared ones
+    assert!(server.client(1).get_bot_comments("1".to_string()).await.unwrap().is_empty());
+
+    let links: Vec<ShortedLink> = serde_json::from_str(include_str!("fixtures/v2/shorted_links.json")).unwrap();
+    assert_eq!(server.client(2).get_my_shorted_links().await.unwrap(), links);
+
+    std::fs::write(dir.join("broken.json"), "{").unwrap();
+    assert!(MockServer::builder().fixtures_dir(&dir).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Running `boticord-mock`, killed on drop.
+struct Binary(Child);
+
+impl Drop for Binary {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn binary(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_boticord-mock"));
+    command.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
+    command
+}
+
+#[tokio::test]
+async fn runs_standalone() {
+    let dir = fixtures("bin");
+    let scenario = dir.join("scenario.txt");
+    std::fs::write(&scenario, r#"{ "steps": [{ "route": "/stats", "status": 503 }] }"#).unwrap();
+    let args = ["--port", "0", "--fixtures", dir.to_str().unwrap(), "--token", "secret", "--scenario", scenario.to_str().unwrap()];
+    let mut child = Binary(binary(&args).spawn().unwrap());
+
+    let mut line = String::new();
+    BufReader::new(child.0.stdout.take().unwrap()).read_line(&mut line).unwrap();
+    let url = line.trim().rsplit(' ').next().unwrap().to_string();
+    assert!(url.starts_with("http://127.0.0.1:"), "{}", line);
+
+    let client = BoticordClient::new("secret", 2).unwrap().with_base_url(&url);
+    assert_eq!(client.get_bot_comments("1".to_string()).await.unwrap().len(), 1);
+    assert_eq!(client.post_bot_stats(stats()).await.unwrap_err().status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    client.post_bot_stats(stats()).await.expect("failed post");
+    let stranger = BoticordClient::new("other", 2).unwrap().with_base_url(&url);
+    assert_eq!(stranger.get_bot_comments("1".to_string()).await.unwrap_err().status(), Some(StatusCode::UNAUTHORIZED));
+
+    drop(child);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    let help = binary(&["--help"]).output().unwrap();
+    assert!(help.status.success());
+    assert!(String::from_utf8_lossy(&help.stdout).starts_with("usage: boticord-mock"));
+
+    for args in [&["--port"][..], &["--port", "x"], &["--fixtures", "/nonexistent"], &["--wat", "1"]] {
+        let output = binary(args).output().unwrap();
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("usage:"), "{:?}", args);
+    }
+}
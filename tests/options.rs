@@ -0,0 +1,121 @@
+//! Options of individual calls.
+
+use boticordrs::cache::Cache;
+use boticordrs::options::{CancellationToken, RequestOptions};
+use boticordrs::retry::RetryPolicy;
+use boticordrs::types::BotStats;
+use boticordrs::{BoticordClient, BoticordError};
+use http::{HeaderName, HeaderValue};
+
+use std::time::Duration;
+
+mod common;
+
+use common::{response, Scripted};
+
+#[tokio::test]
+async fn times_out_single_calls() {
+    let transport = Scripted::always(200, "[]").delay(Duration::from_millis(200));
+    let client = BoticordClient::new_with_transport(transport, "token", 2);
+
+    let stats = BotStats { servers: 1, shards: 1, users: 1 };
+    let options = RequestOptions::new().timeout(Duration::from_millis(20));
+    let result = client.with_options(options, |client| client.post_bot_stats(stats)).await;
+    assert!(matches!(result, Err(BoticordError::Timeout(_))));
+
+    // the client itself has no timeout
+    client.get_bot_comments("1".to_string()).await.expect("failed get");
+}
+
+#[tokio::test]
+async fn adds_headers_and_overrides_retries() {
+    let transport = Scripted::new().reply(503, "").otherwise(|_, _| Ok(response(200, "[]")));
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2);
+    let options = RequestOptions::new()
+        .header(HeaderName::from_static("x-correlation-id"), HeaderValue::from_static("42"))
+        .header(http::header::AUTHORIZATION, HeaderValue::from_static("stolen"))
+        .retry(RetryPolicy { max_retries: 1, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(1), ..RetryPolicy::none() });
+
+    client.with_options(options, |client| client.get_bot_comments("1".to_string())).await.expect("failed get");
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].headers["x-correlation-id"], "42");
+    assert_eq!(requests[1].headers["authorization"], "Bot token");
+}
+
+#[tokio::test]
+async fn cancels_calls() {
+    let transport = Scripted::always(200, "[]").delay(Duration::from_secs(10));
+    let client = BoticordClient::new_with_transport(transport, "token", 2);
+    let cancel = CancellationToken::new();
+
+    let call = {
+        let options = RequestOptions::new().cancel_on(cancel.clone());
+        tokio::spawn(async move { client.with_options(options, |client| client.get_bot_comments("1".to_string())).await })
+    };
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    cancel.cancel();
+
+    let result = tokio::time::timeout(Duration::from_secs(1), call).await.unwrap().unwrap();
+    assert!(matches!(result, Err(BoticordError::Cancelled)));
+    assert!(cancel.is_cancelled());
+}
+
+/// Answers every request with a comment list whose length is the request number.
+fn counting() -> Scripted {
+    Scripted::new().otherwise(|_, n| {
+        let comments = vec![serde_json::json!({"userID": "1", "text": "hi", "vote": 1, "isUpdated": false}); n];
+        Ok(response(200, serde_json::to_vec(&comments).unwrap()))
+    })
+}
+
+#[tokio::test]
+async fn bypasses_the_cache() {
+    let transport = counting();
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2)
+        .with_cache(Cache::new(10).with_default_ttl(Duration::from_secs(60)));
+
+    client.get_bot_comments("1".to_string()).await.expect("failed get");
+    client.get_bot_comments("1".to_string()).await.expect("failed get");
+    assert_eq!(transport.calls(), 1);
+
+    let options = RequestOptions::new().bypass_cache(true);
+    let fresh = client
+        .with_options(options, |client| client.get_bot_comments_with_meta("1".to_string()))
+        .await
+        .expect("failed get");
+    assert!(!fresh.from_cache);
+    assert_eq!((fresh.value.len(), transport.calls()), (2, 2));
+
+    // not written to the cache either
+    let cached = client.get_bot_comments_with_meta("1".to_string()).await.expect("failed get");
+    assert!(cached.from_cache);
+    assert_eq!(cached.value.len(), 1);
+}
+
+#[tokio::test]
+async fn refreshes_in_background_without_the_caller_options() {
+    let transport = counting().delay(Duration::from_millis(50));
+    let cache = Cache::new(10)
+        .with_default_ttl(Duration::from_millis(10))
+        .with_stale_while_revalidate(Duration::from_secs(60));
+    let client = BoticordClient::new_with_transport(transport.clone(), "token", 2).with_cache(cache);
+    client.get_bot_comments("1".to_string()).await.expect("failed get");
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // answered from the cache before the timeout and the cancellation
+    let cancel = CancellationToken::new();
+    let options = RequestOptions::new()
+        .timeout(Duration::from_millis(20))
+        .cancel_on(cancel.clone())
+        .header(HeaderName::from_static("x-correlation-id"), HeaderValue::from_static("42"));
+    let stale = client.with_options(options, |client| client.get_bot_comments("1".to_string())).await;
+    assert_eq!(stale.expect("failed stale get").len(), 1);
+    cancel.cancel();
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(transport.calls(), 2);
+    assert!(!transport.requests()[1].headers.contains_key("x-correlation-id"));
+    assert_eq!(client.get_bot_comments("1".to_string()).await.expect("failed get").len(), 2);
+}
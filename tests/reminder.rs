@@ -0,0 +1,131 @@
+//! Bump reminders.
+
+use boticordrs::reminder::{Reminder, ReminderScheduler, ReminderStore};
+use boticordrs::store::{JsonFileStore, MemoryStore};
+use boticordrs::types::{Server, ServerId, UserId};
+use boticordrs::BoticordError;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Keeps saved reminders in memory, failing saves while `broken` is set.
+#[derive(Clone, Default)]
+struct Flaky {
+    saved: Arc<Mutex<Vec<Reminder>>>,
+    broken: Arc<AtomicBool>,
+}
+
+impl ReminderStore for Flaky {
+    fn load(&self) -> Result<Vec<Reminder>, BoticordError> {
+        Ok(self.saved.lock().unwrap().clone())
+    }
+
+    fn save(&self, reminders: &[Reminder]) -> Result<(), BoticordError> {
+        if self.broken.load(Ordering::SeqCst) {
+            return Err(BoticordError::Store(std::io::Error::other("disk is full")));
+        }
+        *self.saved.lock().unwrap() = reminders.to_vec();
+        Ok(())
+    }
+}
+
+fn user(id: &str) -> UserId {
+    UserId(id.to_string())
+}
+
+fn server() -> ServerId {
+    ServerId("1".to_string())
+}
+
+fn past() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1)
+}
+
+fn future() -> SystemTime {
+    SystemTime::now() + Duration::from_secs(3600)
+}
+
+#[tokio::test]
+async fn fires_due_reminders_after_the_callback() {
+    let store = Flaky::default();
+    let scheduler = ReminderScheduler::new(store.clone()).unwrap();
+    scheduler.schedule(user("a"), server(), past()).unwrap();
+    scheduler.schedule(user("b"), server(), future()).unwrap();
+
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let run = tokio::spawn({
+        let scheduler = scheduler.clone();
+        let store = store.clone();
+        let fired = fired.clone();
+        async move {
+            scheduler.run(move |reminder| {
+                // still saved while the callback runs
+                assert!(store.saved.lock().unwrap().contains(&reminder));
+                fired.lock().unwrap().push(reminder.user.0);
+                async {}
+            }).await
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    run.abort();
+
+    assert_eq!(*fired.lock().unwrap(), ["a"]);
+    let pending: Vec<_> = store.saved.lock().unwrap().iter().map(|r| r.user.0.clone()).collect();
+    assert_eq!(pending, ["b"]);
+}
+
+#[tokio::test]
+async fn keeps_reminders_when_saving_fails() {
+    let store = Flaky::default();
+    let scheduler = ReminderScheduler::new(store.clone()).unwrap();
+    scheduler.schedule(user("a"), server(), past()).unwrap();
+
+    store.broken.store(true, Ordering::SeqCst);
+    assert!(scheduler.schedule(user("b"), server(), future()).is_err());
+    assert!(scheduler.cancel(&user("a"), &server()).is_err());
+    assert_eq!(scheduler.pending().len(), 1);
+
+    // delivered, but kept to fire again after a restart
+    let mut fired = 0;
+    let result = scheduler.run(|_| {
+        fired += 1;
+        async {}
+    }).await;
+    assert!(matches!(result, Err(BoticordError::Store(_))));
+    assert_eq!(fired, 1);
+    assert_eq!(scheduler.pending().len(), 1);
+    assert_eq!(store.saved.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn persists_reminders() {
+    let path = std::env::temp_dir().join(format!("boticordrs-reminders-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let scheduler = ReminderScheduler::new(JsonFileStore::new(&path)).unwrap();
+    scheduler.schedule(user("a"), server(), future()).unwrap();
+    scheduler.schedule(user("a"), server(), future() + Duration::from_secs(60)).unwrap();
+    drop(scheduler);
+
+    let scheduler = ReminderScheduler::new(JsonFileStore::new(&path)).unwrap();
+    let pending = scheduler.pending();
+    assert_eq!(pending.len(), 1);
+    assert!(pending[0].due_time() > future() + Duration::from_secs(50));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn remembers_bump_counts() {
+    let server: Server = serde_json::from_str(include_str!("fixtures/v2/server.json")).unwrap();
+    let scheduler = ReminderScheduler::new(MemoryStore).unwrap();
+    scheduler.schedule_server(user("a"), &server, future()).unwrap();
+    scheduler.schedule(user("b"), server.id.clone(), future()).unwrap();
+
+    let pending = scheduler.pending();
+    assert_eq!(pending[0].bumps, Some(91));
+    assert!(!pending[0].bumped_since(91));
+    assert!(pending[0].bumped_since(92));
+    assert!(!pending[1].bumped_since(92));
+}
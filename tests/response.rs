@@ -0,0 +1,116 @@
+//! Responses returned together with their metadata.
+
+use boticordrs::cache::Cache;
+use boticordrs::response::RateLimit;
+use boticordrs::transport::HttpResponse;
+use boticordrs::types::BotStats;
+use boticordrs::{BoticordClient, BoticordError};
+use http::{HeaderMap, HeaderValue, StatusCode};
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod common;
+
+use common::{response, Scripted};
+
+/// Answers every request with an empty comment list and the headers.
+fn with_headers(pairs: &[(&'static str, &'static str)]) -> Scripted {
+    Scripted::always_respond(HttpResponse { headers: headers(pairs), ..response(200, "[]") })
+}
+
+fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+    pairs.iter().map(|(name, value)| (name.parse().unwrap(), HeaderValue::from_static(value))).collect()
+}
+
+#[tokio::test]
+async fn returns_status_rate_limit_and_request_id() {
+    let transport = with_headers(&[
+        ("x-ratelimit-limit", "60"),
+        ("x-ratelimit-remaining", "59"),
+        ("x-ratelimit-reset", "30"),
+        ("x-request-id", "abc"),
+    ]);
+    let client = BoticordClient::new_with_transport(transport, "token", 2);
+
+    let resp = client.get_bot_comments_with_meta("1".to_string()).await.unwrap();
+    assert!(resp.value.is_empty());
+    assert_eq!(resp.status, StatusCode::OK);
+    assert_eq!(resp.request_id.as_deref(), Some("abc"));
+    assert!(!resp.from_cache);
+
+    let limit = resp.rate_limit.unwrap();
+    assert_eq!((limit.limit, limit.remaining), (Some(60), Some(59)));
+    let reset = limit.reset.unwrap().duration_since(SystemTime::now()).unwrap();
+    assert!(reset <= Duration::from_secs(30) && reset > Duration::from_secs(25));
+
+    let resp = client.post_bot_stats_with_meta(BotStats { servers: 1, shards: 1, users: 1 }).await.unwrap();
+    assert_eq!(resp.request_id.as_deref(), Some("abc"));
+}
+
+#[test]
+fn parses_rate_limit_headers() {
+    assert_eq!(RateLimit::from_headers(&HeaderMap::new()), None);
+
+    let limit = RateLimit::from_headers(&headers(&[
+        ("ratelimit-remaining", "0"),
+        ("ratelimit-reset", "1700000000"),
+    ]))
+    .unwrap();
+    assert_eq!(limit.limit, None);
+    assert_eq!(limit.remaining, Some(0));
+    assert_eq!(limit.reset, Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+}
+
+#[tokio::test]
+async fn reports_cached_responses() {
+    let transport = with_headers(&[("cf-ray", "ray")]);
+    let client = BoticordClient::new_with_transport(transport, "token", 2)
+        .with_cache(Cache::new(10).with_default_ttl(Duration::from_secs(60)));
+
+    let first = client.get_server_comments_with_meta("1".to_string()).await.unwrap();
+    let second = client.get_server_comments_with_meta("1".to_string()).await.unwrap();
+    assert!(!first.from_cache);
+    assert!(second.from_cache && !second.stale);
+    assert_eq!(second.request_id.as_deref(), Some("ray"));
+}
+
+#[tokio::test]
+async fn takes_metadata_from_revalidations() {
+    let stored = HttpResponse {
+        headers: headers(&[("etag", "\"v1\""), ("x-request-id", "first"), ("x-ratelimit-remaining", "59")]),
+        ..response(200, "[]")
+    };
+    let not_modified = HttpResponse {
+        headers: headers(&[("x-request-id", "second"), ("x-ratelimit-remaining", "58"), ("content-length", "0")]),
+        ..response(304, "")
+    };
+    let transport = Scripted::new().respond(stored).respond(not_modified);
+    let client = BoticordClient::new_with_transport(transport, "token", 2)
+        .with_cache(Cache::new(10).with_default_ttl(Duration::from_millis(10)));
+
+    client.get_bot_comments_with_meta("1".to_string()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let resp = client.get_bot_comments_with_meta("1".to_string()).await.unwrap();
+    assert!(resp.value.is_empty());
+    assert_eq!(resp.status, StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.request_id.as_deref(), Some("second"));
+    assert_eq!(resp.rate_limit.unwrap().remaining, Some(58));
+    assert_eq!(resp.headers["etag"], "\"v1\"");
+    assert!(resp.headers.get("content-length").is_none());
+}
+
+#[tokio::test]
+async fn reports_rate_limits_of_errors() {
+    let transport = Scripted::always_respond(HttpResponse {
+        headers: headers(&[("x-ratelimit-remaining", "0"), ("x-request-id", "abc")]),
+        ..response(429, "{\"error\":\"slow down\"}")
+    });
+    let client = BoticordClient::new_with_transport(transport, "token", 2);
+
+    let error = client.post_bot_stats(BotStats { servers: 1, shards: 1, users: 1 }).await.unwrap_err();
+    assert_eq!(error.rate_limit().and_then(|limit| limit.remaining), Some(0));
+    match error {
+        BoticordError::Api(e) => assert_eq!(e.request_id.as_deref(), Some("abc")),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
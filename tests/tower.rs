@@ -0,0 +1,102 @@
+//! Client over tower service stacks.
+#![cfg(feature = "tower")]
+
+use boticordrs::service::ApiCall;
+use boticordrs::transport::{HttpRequest, HttpResponse};
+use boticordrs::{BoticordClient, BoticordError};
+use http::{HeaderMap, StatusCode};
+use tower::util::BoxService;
+use tower::{Service, ServiceBuilder, ServiceExt};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn ok_service(
+    calls: Arc<AtomicUsize>,
+    delay: Duration,
+) -> impl tower::Service<HttpRequest, Response = HttpResponse, Error = BoticordError, Future = impl Send + 'static>
+       + Clone
+       + Send
+       + 'static {
+    tower::service_fn(move |_request: HttpRequest| {
+        calls.fetch_add(1, Ordering::SeqCst);
+        async move {
+            tokio::time::sleep(delay).await;
+            Ok::<_, BoticordError>(HttpResponse {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: b"[]".to_vec(),
+            })
+        }
+    })
+}
+
+#[tokio::test]
+async fn calls_go_through_layers() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let stack = ServiceBuilder::new()
+        .concurrency_limit(1)
+        .service(ok_service(Arc::clone(&calls), Duration::ZERO));
+    let client = BoticordClient::new_with_service(stack, "token".to_string(), 2);
+
+    let bots = client.get_user_bots("1".to_string()).await.expect("failed get");
+    assert!(bots.is_empty());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn layer_errors_become_transport_errors() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let stack = ServiceBuilder::new()
+        .timeout(Duration::from_millis(10))
+        .service(ok_service(calls, Duration::from_secs(5)));
+    let client = BoticordClient::new_with_service(stack, "token".to_string(), 2);
+
+    let result = client.get_user_bots("1".to_string()).await;
+    assert!(matches!(result, Err(BoticordError::Transport(_))), "{:?}", result);
+}
+
+#[tokio::test]
+async fn service_errors_are_passed_through() {
+    let stack = tower::service_fn(|_request: HttpRequest| async {
+        Err::<HttpResponse, _>(BoticordError::Validation("rejected".to_string()))
+    });
+    let client = BoticordClient::new_with_service(stack, "token".to_string(), 2);
+
+    let result = client.get_user_bots("1".to_string()).await;
+    assert!(matches!(result, Err(BoticordError::Validation(_))), "{:?}", result);
+}
+
+#[tokio::test]
+async fn boxed_services_are_accepted() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let stack = BoxService::new(ok_service(Arc::clone(&calls), Duration::from_millis(50)));
+    let client = BoticordClient::new_with_service(stack, "token".to_string(), 2);
+
+    // the calls only take turns to start
+    let started = Instant::now();
+    let (first, second) = tokio::join!(client.get_user_bots("1".to_string()), client.get_user_bots("2".to_string()));
+    assert!(first.is_ok() && second.is_ok());
+    assert!(started.elapsed() < Duration::from_millis(100));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn layers_wrap_whole_calls() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let client = BoticordClient::new_with_service(ok_service(Arc::clone(&calls), Duration::ZERO), "token".to_string(), 2);
+    let mut service = ServiceBuilder::new().concurrency_limit(1).service(client);
+
+    let resp = service.ready().await.unwrap().call(ApiCall::get("/bots/1")).await.expect("failed call");
+    assert_eq!((resp.status, resp.value), (StatusCode::OK, serde_json::json!([])));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // API errors and decoding happen inside the service
+    let failing = tower::service_fn(|_request: HttpRequest| async {
+        Ok::<_, BoticordError>(HttpResponse { status: StatusCode::NOT_FOUND, headers: HeaderMap::new(), body: b"{}".to_vec() })
+    });
+    let mut service = BoticordClient::new_with_service(failing, "token".to_string(), 2);
+    let error = service.ready().await.unwrap().call(ApiCall::post("/links/get", serde_json::json!({}))).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+}
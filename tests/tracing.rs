@@ -0,0 +1,90 @@
+//! Spans and events emitted for API calls.
+#![cfg(feature = "tracing")]
+
+use boticordrs::BoticordClient;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::MakeWriter;
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::Scripted;
+
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Output {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Output {
+    type Writer = Output;
+
+    fn make_writer(&'a self) -> Output {
+        self.clone()
+    }
+}
+
+async fn capture(client: BoticordClient) -> String {
+    let output = Output::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(output.clone())
+        .with_max_level(tracing::Level::TRACE)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+    let _ = client.get_bot_comments("42".to_string()).await;
+    output.text()
+}
+
+#[tokio::test]
+async fn records_calls() {
+    let client = BoticordClient::new_with_transport(Scripted::always(200, "[]"), "secret-token".to_string(), 2);
+    let output = capture(client).await;
+
+    assert!(output.contains("boticord.request"), "{}", output);
+    assert!(output.contains("method=\"get_bot_comments\""), "{}", output);
+    assert!(output.contains("route=\"/bot/{}/comments\""), "{}", output);
+    assert!(output.contains("id=\"42\""), "{}", output);
+    assert!(output.contains("http.status=200"), "{}", output);
+    assert!(output.contains("attempts=1"), "{}", output);
+    assert!(!output.contains("response body"), "{}", output);
+    assert!(!output.contains("secret-token"), "{}", output);
+}
+
+#[tokio::test]
+async fn logs_bodies_when_enabled() {
+    let client = BoticordClient::new_with_transport(Scripted::always(404, "{\"error\":\"gone\"}"), "secret-token".to_string(), 2)
+        .with_body_logging(true);
+    let output = capture(client).await;
+
+    assert!(output.contains("WARN"), "{}", output);
+    assert!(output.contains("http.status=404"), "{}", output);
+    assert!(output.contains("gone"), "{}", output);
+    assert!(!output.contains("secret-token"), "{}", output);
+}
+
+#[tokio::test]
+async fn keeps_error_bodies_out_when_disabled() {
+    let client = BoticordClient::new_with_transport(Scripted::always(404, "{\"error\":\"gone\"}"), "secret-token".to_string(), 2);
+    let output = capture(client).await;
+
+    let warning = output.lines().find(|line| line.contains("WARN")).expect("no warning");
+    assert!(warning.contains(" status=404 kind=\"api\""), "{}", output);
+    assert!(!output.contains("gone"), "{}", output);
+}
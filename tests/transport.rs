@@ -0,0 +1,126 @@
+//! Client logic over a scripted in-memory transport.
+
+use boticordrs::retry::RetryPolicy;
+use boticordrs::types::BotStats;
+use boticordrs::{BoticordClient, BoticordError};
+use http::{Method, StatusCode};
+
+use std::time::Duration;
+
+mod common;
+
+use common::Scripted;
+
+fn client(transport: &Scripted) -> BoticordClient {
+    BoticordClient::new_with_transport(transport.clone(), "token".to_string(), 2)
+        .with_base_url("http://boticord.test")
+}
+
+#[tokio::test]
+async fn sends_authorized_requests() {
+    let transport = Scripted::replies(&[(200, "{}")]);
+    client(&transport)
+        .post_bot_stats(BotStats { servers: 1, shards: 2, users: 3 })
+        .await
+        .expect("failed post");
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].url, "http://boticord.test/v2/stats");
+    assert_eq!(requests[0].headers["authorization"], "Bot token");
+    assert_eq!(requests[0].headers["content-type"], "application/json");
+    let body: serde_json::Value = serde_json::from_slice(requests[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(body, serde_json::json!({ "servers": 1, "shards": 2, "users": 3 }));
+}
+
+#[tokio::test]
+async fn maps_errors() {
+    let transport = Scripted::replies(&[(404, "{\"error\":\"not found\"}"), (200, "not json")]);
+    let client = client(&transport);
+
+    match client.get_bot_comments("1".to_string()).await {
+        Err(BoticordError::Api(e)) => {
+            assert_eq!(e.status, StatusCode::NOT_FOUND);
+            assert_eq!(e.body, "{\"error\":\"not found\"}");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(matches!(client.get_bot_comments("1".to_string()).await, Err(BoticordError::Decode(_))));
+    assert!(matches!(client.get_bot_comments("1".to_string()).await, Err(BoticordError::Transport(_))));
+}
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let transport = Scripted::replies(&[(503, ""), (429, ""), (200, "[]")]);
+    let policy = RetryPolicy { max_retries: 2, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(5), ..RetryPolicy::none() };
+    let comments = client(&transport)
+        .with_retry_policy(policy)
+        .get_bot_comments("1".to_string())
+        .await
+        .expect("failed get");
+
+    assert!(comments.is_empty());
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn does_not_retry_by_default() {
+    let transport = Scripted::replies(&[(503, ""), (200, "[]")]);
+    let result = client(&transport).get_bot_comments("1".to_string()).await;
+
+    assert_eq!(result.unwrap_err().status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn retries_posts_only_when_rate_limited() {
+    let stats = BotStats { servers: 1, shards: 1, users: 1 };
+    let policy = RetryPolicy { max_retries: 2, base_delay: Duration::from_millis(1), ..RetryPolicy::none() };
+
+    let transport = Scripted::replies(&[(429, ""), (503, ""), (200, "{}")]);
+    let result = client(&transport).with_retry_policy(policy.clone()).post_bot_stats(stats.clone()).await;
+    assert_eq!(result.unwrap_err().status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(transport.requests().len(), 2);
+
+    let transport = Scripted::replies(&[(503, ""), (200, "{}")]);
+    client(&transport)
+        .with_retry_policy(policy.with_non_idempotent(true))
+        .post_bot_stats(stats)
+        .await
+        .expect("failed post");
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn calls_raw_endpoints() {
+    let transport = Scripted::replies(&[(200, "{\"likes\":3}"), (503, ""), (204, ""), (404, "{}")]);
+    let policy = RetryPolicy { max_retries: 1, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(5), ..RetryPolicy::none() };
+    let client = client(&transport).with_retry_policy(policy);
+
+    let likes = client
+        .call_value(Method::POST, "/bot/1/likes", Some(serde_json::json!({ "limit": 1 })))
+        .await
+        .expect("failed call");
+    assert_eq!(likes, serde_json::json!({ "likes": 3 }));
+
+    // retried, and an empty body decodes into `()`
+    client.call::<(), ()>(Method::DELETE, "bot/1/likes", None).await.expect("failed call");
+
+    let result = client.call::<(), serde_json::Value>(Method::GET, "/bot/2", None).await;
+    assert_eq!(result.unwrap_err().status(), Some(StatusCode::NOT_FOUND));
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].url, "http://boticord.test/v2/bot/1/likes");
+    assert_eq!(requests[0].headers["authorization"], "Bot token");
+    assert_eq!(requests[0].body.as_deref(), Some(&b"{\"limit\":1}"[..]));
+    assert_eq!((&requests[2].method, requests[2].url.as_str()), (&Method::DELETE, "http://boticord.test/v2/bot/1/likes"));
+    assert_eq!(requests[3].url, "http://boticord.test/v2/bot/2");
+}
+
+#[tokio::test]
+async fn keeps_raw_calls_on_the_api_host() {
+    let transport = Scripted::replies(&[]);
+    let result = client(&transport).call_value(Method::GET, "https://example.com/steal", None).await;
+
+    assert!(matches!(result, Err(BoticordError::Validation(_))));
+    assert!(transport.requests().is_empty());
+}
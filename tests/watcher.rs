@@ -0,0 +1,128 @@
+//! Comment change feed.
+
+use boticordrs::store::{JsonFileStore, MemoryStore};
+use boticordrs::types::{BotId, ServerId};
+use boticordrs::watcher::{CommentEvent, CommentSnapshot, CommentStore, CommentTarget, CommentWatcher};
+use boticordrs::{BoticordClient, BoticordError};
+use futures_util::StreamExt;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+use common::Scripted;
+
+/// Fails every save while the flag is set.
+struct Broken(Arc<AtomicBool>);
+
+impl CommentStore for Broken {
+    fn load(&self) -> Result<Vec<CommentSnapshot>, BoticordError> {
+        Ok(Vec::new())
+    }
+
+    fn save(&self, _snapshots: &[CommentSnapshot]) -> Result<(), BoticordError> {
+        if self.0.load(Ordering::SeqCst) {
+            return Err(BoticordError::Store(std::io::Error::other("disk is full")));
+        }
+        Ok(())
+    }
+}
+
+fn comment(user: &str, text: &str, updated_at: Option<u64>) -> Value {
+    json!({"userID": user, "text": text, "vote": 1, "isUpdated": updated_at.is_some(), "created_at": 1, "updated_at": updated_at})
+}
+
+fn bot() -> CommentTarget {
+    CommentTarget::Bot(BotId("1".to_string()))
+}
+
+#[tokio::test]
+async fn reports_added_edited_and_removed_comments() {
+    let transport = Scripted::new()
+        .reply(200, json!([comment("a", "good", None), comment("b", "bad", None)]).to_string())
+        .reply(200, json!([comment("a", "great", Some(2)), comment("c", "new", None)]).to_string());
+    let client = BoticordClient::new_with_transport(transport, "token", 2);
+    let mut watcher = CommentWatcher::new(client, MemoryStore).unwrap().watch(bot());
+
+    // the first poll only takes a snapshot
+    assert!(watcher.poll().await.pop().unwrap().unwrap().is_empty());
+    assert_eq!(watcher.snapshot(&bot()).unwrap().len(), 2);
+
+    let events = watcher.poll().await.pop().unwrap().unwrap();
+    let kinds: Vec<_> = events.iter().map(|e| match e {
+        CommentEvent::Added { comment, .. } => format!("added {}", comment.user_id),
+        CommentEvent::Edited { before, after, .. } => format!("edited {}: {} -> {}", after.user_id, before.text, after.text),
+        CommentEvent::Removed { comment, .. } => format!("removed {}", comment.user_id),
+    }).collect();
+    assert_eq!(kinds, vec!["edited a: good -> great", "added c", "removed b"]);
+    assert!(events.iter().all(|e| *e.target() == bot()));
+}
+
+#[tokio::test]
+async fn resumes_from_persisted_snapshots() {
+    let path = std::env::temp_dir().join(format!("boticordrs-comments-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let transport = Scripted::new()
+        .reply(200, json!([comment("a", "good", None)]).to_string())
+        .reply(200, json!([comment("a", "good", None), comment("b", "hi", None)]).to_string());
+    let client = BoticordClient::new_with_transport(transport, "token", 2);
+
+    let mut watcher = CommentWatcher::new(client.clone(), JsonFileStore::new(&path)).unwrap().watch(bot());
+    watcher.poll().await.pop().unwrap().unwrap();
+    drop(watcher);
+
+    // after a restart only the new comment is reported
+    let mut watcher = CommentWatcher::new(client, JsonFileStore::new(&path)).unwrap().watch(bot());
+    let events = watcher.poll().await.pop().unwrap().unwrap();
+    assert!(matches!(&events[..], [CommentEvent::Added { comment, .. }] if comment.user_id == "b"));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn streams_events_and_errors() {
+    let transport = Scripted::new()
+        .reply(200, "[]")
+        .reply(200, "[]")
+        .reply(503, "")
+        .reply(200, json!([comment("a", "hi", None)]).to_string());
+    let client = BoticordClient::new_with_transport(transport, "token", 2);
+    let mut events = CommentWatcher::new(client, MemoryStore)
+        .unwrap()
+        .watch(bot())
+        .watch_server(ServerId("2".to_string()))
+        .with_interval(Duration::from_millis(1))
+        .into_stream();
+
+    let error = events.next().await.unwrap().unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    match events.next().await.unwrap().unwrap() {
+        CommentEvent::Added { target, comment } => {
+            assert_eq!(target, CommentTarget::Server(ServerId("2".to_string())));
+            assert_eq!(comment.user_id, "a");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn reports_events_again_after_a_failed_save() {
+    let added = json!([comment("a", "hi", None)]).to_string();
+    let transport = Scripted::new().reply(200, "[]").reply(200, added.clone()).reply(200, added);
+    let client = BoticordClient::new_with_transport(transport, "token", 2);
+    let broken = Arc::new(AtomicBool::new(false));
+    let mut watcher = CommentWatcher::new(client, Broken(broken.clone())).unwrap().watch(bot());
+    watcher.poll().await.pop().unwrap().unwrap();
+
+    broken.store(true, Ordering::SeqCst);
+    assert!(matches!(watcher.poll().await.pop().unwrap(), Err(BoticordError::Store(_))));
+    assert_eq!(watcher.snapshot(&bot()).unwrap().len(), 0);
+
+    broken.store(false, Ordering::SeqCst);
+    let events = watcher.poll().await.pop().unwrap().unwrap();
+    assert!(matches!(&events[..], [CommentEvent::Added { comment, .. }] if comment.user_id == "a"));
+}